@@ -0,0 +1,327 @@
+use crate::core::board::{Board, Disk, Position};
+use crate::core::rules::RuleSet;
+
+/// The number of empty squares from which `search` reads the game out to
+/// the end instead of using the evaluation function.
+pub const ENDGAME_EMPTIES: usize = 10;
+
+const WIN_SCALE: i32 = 1000;
+
+/// The square weights of the standard board.
+#[rustfmt::skip]
+const WEIGHTS: [i32; 64] = [
+    100, -20, 10,  5,  5, 10, -20, 100,
+    -20, -50, -2, -2, -2, -2, -50, -20,
+     10,  -2,  1,  1,  1,  1,  -2,  10,
+      5,  -2,  1,  0,  0,  1,  -2,   5,
+      5,  -2,  1,  0,  0,  1,  -2,   5,
+     10,  -2,  1,  1,  1,  1,  -2,  10,
+    -20, -50, -2, -2, -2, -2, -50, -20,
+    100, -20, 10,  5,  5, 10, -20, 100,
+];
+
+/// The weight of a square, from `WEIGHTS` on the standard board and by
+/// the distance from the edges on boards of other sizes: corners are worth
+/// the most and the squares next to them the least.
+fn weight(size: usize, pos: &Position) -> i32 {
+    if size == Board::DEFAULT_SIZE {
+        return WEIGHTS[pos.index(size)];
+    }
+
+    let last = size as i32 - 1;
+    let (dx, dy) = (pos.x.min(last - pos.x), pos.y.min(last - pos.y));
+    match (dx.min(dy), dx.max(dy)) {
+        (0, 0) => 100,
+        (0, 1) => -20,
+        (1, 1) => -50,
+        (0, _) => 10,
+        (1, _) => -2,
+        _ => 1,
+    }
+}
+
+fn opponent(disk: Disk) -> Disk {
+    let mut disk = disk;
+    disk.reverse();
+    disk
+}
+
+pub fn count_empties(board: &Board) -> usize {
+    board.size() * board.size() - board.count_disks(&Disk::Dark) - board.count_disks(&Disk::Light)
+}
+
+/// Returns the difference of the number of disks from the view of `disk`.
+pub fn disk_diff(board: &Board, disk: Disk) -> i32 {
+    board.count_disks(&disk) as i32 - board.count_disks(&opponent(disk)) as i32
+}
+
+/// Evaluates a position heuristically from the view of `disk` by the
+/// square weights and the mobility of both sides.
+///
+/// Under `RuleSet::AntiReversi`, where the fewest disks win, the square
+/// weights are counted against the player instead, but mobility is still
+/// worth having.
+pub fn evaluate(board: &Board, disk: Disk, rules: RuleSet) -> i32 {
+    let opp = opponent(disk);
+    let mut score = 0;
+    for pos in board.positions() {
+        match board.get(&pos) {
+            Some(d) if *d == disk => score += weight(board.size(), &pos),
+            Some(_) => score -= weight(board.size(), &pos),
+            None => (),
+        }
+    }
+
+    rules.score(score) + 5 * (board.count_legal_movs(disk) - board.count_legal_movs(opp))
+}
+
+/// Searches the best move for `disk` under `rules` with an alpha-beta
+/// search of `depth` plies, or an exact search once few enough squares are
+/// left.
+///
+/// The score is the final disk difference for an exact search and the
+/// heuristic evaluation otherwise, both turned by `RuleSet::score` so that
+/// a higher score is better for `disk`. Returns `None` if `disk` has to
+/// pass.
+pub fn search(board: &Board, disk: Disk, depth: u32, rules: RuleSet) -> Option<(Position, i32)> {
+    if count_empties(board) <= ENDGAME_EMPTIES {
+        solve(board, disk, rules)
+    } else {
+        search_root(board, disk, depth, false, rules)
+    }
+}
+
+/// Reads the game out to the end and returns the best move for `disk` with
+/// the final disk difference it leads to, scored by `rules`. Returns `None`
+/// if `disk` has to pass.
+pub fn solve(board: &Board, disk: Disk, rules: RuleSet) -> Option<(Position, i32)> {
+    search_root(board, disk, count_empties(board) as u32, true, rules)
+}
+
+/// Scores each legal move of `disk` like `search` does.
+pub fn score_movs(board: &Board, disk: Disk, depth: u32, rules: RuleSet) -> Vec<(Position, i32)> {
+    let exact = count_empties(board) <= ENDGAME_EMPTIES;
+    let depth = if exact {
+        count_empties(board) as u32
+    } else {
+        depth
+    };
+
+    board
+        .legal_movs(disk)
+        .into_iter()
+        .map(|pos| {
+            let mut next = board.clone();
+            next.place(pos.clone(), disk)
+                .expect("A legal move must be able to place.");
+            let score = -negamax(
+                &next,
+                opponent(disk),
+                depth.saturating_sub(1),
+                -i32::MAX,
+                i32::MAX,
+                exact,
+                rules,
+            );
+            (pos, score)
+        })
+        .collect()
+}
+
+fn search_root(
+    board: &Board,
+    disk: Disk,
+    depth: u32,
+    exact: bool,
+    rules: RuleSet,
+) -> Option<(Position, i32)> {
+    let mut best: Option<(Position, i32)> = None;
+    let mut alpha = -i32::MAX;
+
+    for pos in board.legal_movs(disk) {
+        let mut next = board.clone();
+        next.place(pos.clone(), disk)
+            .expect("A legal move must be able to place.");
+        let score = -negamax(
+            &next,
+            opponent(disk),
+            depth.saturating_sub(1),
+            -i32::MAX,
+            -alpha,
+            exact,
+            rules,
+        );
+        if best.as_ref().is_none_or(|(_, s)| score > *s) {
+            alpha = alpha.max(score);
+            best = Some((pos, score));
+        }
+    }
+
+    best
+}
+
+fn negamax(
+    board: &Board,
+    disk: Disk,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    exact: bool,
+    rules: RuleSet,
+) -> i32 {
+    let movs = board.legal_movs(disk);
+    if movs.is_empty() {
+        let opp = opponent(disk);
+        if !board.exists_legal_mov(opp) {
+            let diff = rules.score(disk_diff(board, disk));
+            return if exact { diff } else { diff * WIN_SCALE };
+        }
+        return -negamax(board, opp, depth, -beta, -alpha, exact, rules);
+    }
+
+    if depth == 0 {
+        return evaluate(board, disk, rules);
+    }
+
+    let mut best = -i32::MAX;
+    for pos in movs {
+        let mut next = board.clone();
+        next.place(pos, disk)
+            .expect("A legal move must be able to place.");
+        let score = -negamax(
+            &next,
+            opponent(disk),
+            depth - 1,
+            -beta,
+            -alpha,
+            exact,
+            rules,
+        );
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_fig;
+    use crate::core::board::notation::parse_suite;
+    use crate::core::board::Disk::{Dark, Light};
+
+    #[test]
+    fn test_search_takes_corner() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "_oox____",
+            "________",
+            "________",
+            "___ox___",
+            "___xo___",
+            "________",
+            "________",
+            "________"
+        );
+
+        let (pos, _) = search(&board, Dark, 2, RuleSet::Standard).unwrap();
+        assert_eq!(pos, Position::new(0, 0));
+    }
+
+    #[test]
+    fn test_search_on_small_board() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "_oox__",
+            "______",
+            "__ox__",
+            "__xo__",
+            "______",
+            "______"
+        );
+
+        assert_eq!(count_empties(&board), 29);
+        assert_eq!(weight(6, &Position::new(5, 0)), 100);
+        assert_eq!(weight(6, &Position::new(4, 1)), -50);
+        let (pos, _) = search(&board, Dark, 2, RuleSet::Standard).unwrap();
+        assert_eq!(pos, Position::new(0, 0));
+    }
+
+    #[test]
+    fn test_solve() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxo",
+            "xxxxxx__"
+        );
+
+        assert_eq!(solve(&board, Light, RuleSet::Standard), None);
+        assert_eq!(
+            solve(&board, Dark, RuleSet::Standard),
+            Some((Position::new(7, 7), 63))
+        );
+    }
+
+    #[test]
+    fn test_anti_reversi() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "_oox____",
+            "________",
+            "________",
+            "___ox___",
+            "___xo___",
+            "________",
+            "________",
+            "________"
+        );
+        let (pos, _) = search(&board, Dark, 2, RuleSet::AntiReversi).unwrap();
+        assert_ne!(pos, Position::new(0, 0));
+
+        let mut corner = board.clone();
+        corner.place(Position::new(0, 0), Dark).unwrap();
+        assert!(evaluate(&corner, Dark, RuleSet::Standard) > 0);
+        assert!(evaluate(&corner, Dark, RuleSet::AntiReversi) < 0);
+
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxo",
+            "xxxxxx__"
+        );
+        assert_eq!(
+            solve(&board, Dark, RuleSet::AntiReversi),
+            Some((Position::new(7, 7), -63))
+        );
+    }
+
+    #[test]
+    fn test_solve_suite() {
+        let suite = "% position; best move:exact score
+            XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXOXXXXXX-- X; h8:+63;
+            OOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOOXOOOOOO-- O; h8:+63;";
+
+        for entry in parse_suite(suite).unwrap() {
+            let disk = entry.state.turn.into_disk();
+            assert_eq!(
+                solve(&entry.state.board, disk, RuleSet::Standard),
+                Some(entry.scores[0].clone())
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::ai::{self, ENDGAME_EMPTIES};
+use crate::core::board::{Board, Position};
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::TurnPlayer;
+use crate::record::GameRecord;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct AnalysisOptions {
+    /// The search depth before the endgame.
+    pub depth: u32,
+    /// A loss of more disks than this is a blunder in the endgame.
+    pub blunder_disks: i32,
+    /// A loss of more evaluation points than this is a blunder before the
+    /// endgame.
+    pub blunder_eval: i32,
+    /// The rules to analyze the game under, usually those of the record.
+    /// Left out when serialized if standard.
+    #[serde(skip_serializing_if = "RuleSet::is_standard")]
+    pub rules: RuleSet,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            blunder_disks: 4,
+            blunder_eval: 40,
+            rules: RuleSet::Standard,
+        }
+    }
+}
+
+/// A move of the game compared with the best move found by the search.
+///
+/// Scores are from the view of the player of the move: the final disk
+/// difference when `exact`, and the heuristic evaluation of `ai::evaluate`
+/// otherwise.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct MoveAnalysis {
+    /// The 1-based number of the move in the record, passes included.
+    pub number: usize,
+    pub turn: TurnPlayer,
+    pub played: Position,
+    pub played_score: i32,
+    pub best: Position,
+    pub best_score: i32,
+    /// `best_score - played_score`, never negative.
+    pub loss: i32,
+    pub exact: bool,
+    pub blunder: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Analysis {
+    pub options: AnalysisOptions,
+    /// The analyzed moves, without passes.
+    pub moves: Vec<MoveAnalysis>,
+}
+
+/// Replays a record and scores every legal move of each position with
+/// `ai::score_movs`, which reads out the game once `ENDGAME_EMPTIES`
+/// squares are left.
+pub fn analyze(record: &GameRecord, options: AnalysisOptions) -> Result<Analysis, String> {
+    let boards = record.boards()?;
+    let mut moves = Vec::new();
+
+    for (i, (mov, board)) in record.moves.iter().zip(&boards).enumerate() {
+        let Some(played) = &mov.pos else {
+            continue;
+        };
+
+        let scores = ai::score_movs(board, mov.turn.into_disk(), options.depth, options.rules);
+        let (best, best_score) = scores
+            .iter()
+            .max_by_key(|(_, score)| *score)
+            .cloned()
+            .expect("A validated move must be legal.");
+        let played_score = scores
+            .iter()
+            .find(|(pos, _)| pos == played)
+            .map(|(_, score)| *score)
+            .expect("A validated move must be scored.");
+
+        let exact = ai::count_empties(board) <= ENDGAME_EMPTIES;
+        let loss = best_score - played_score;
+        let threshold = if exact {
+            options.blunder_disks
+        } else {
+            options.blunder_eval
+        };
+
+        moves.push(MoveAnalysis {
+            number: i + 1,
+            turn: mov.turn,
+            played: played.clone(),
+            played_score,
+            // The played move is the best when it ties.
+            best: if loss == 0 { played.clone() } else { best },
+            best_score,
+            loss,
+            exact,
+            blunder: loss > threshold,
+        });
+    }
+
+    Ok(Analysis { options, moves })
+}
+
+/// Scores every legal move of `turn` like `analyze`, the best first.
+pub fn rank_movs(
+    board: &Board,
+    turn: TurnPlayer,
+    depth: u32,
+    rules: RuleSet,
+) -> Vec<(Position, i32)> {
+    let mut scores = ai::score_movs(board, turn.into_disk(), depth, rules);
+    scores.sort_by_key(|(_, score)| -score);
+    scores
+}
+
+impl Analysis {
+    pub fn blunders(&self) -> impl Iterator<Item = &MoveAnalysis> {
+        self.moves.iter().filter(|mov| mov.blunder)
+    }
+
+    /// Returns a table of the moves with blunders marked by `??`, and a
+    /// summary of the blunders of each player.
+    pub fn to_text(&self) -> String {
+        let mut s = String::from("  #  Player  Move  Score  Best  Score  Loss\n");
+        for mov in &self.moves {
+            let _ = writeln!(
+                s,
+                "{:>3}  {:<6}  {:<4}  {:>+5}  {:<4}  {:>+5}  {:>4}{}{}",
+                mov.number,
+                mov.turn.to_string(),
+                mov.played.to_notation(),
+                mov.played_score,
+                mov.best.to_notation(),
+                mov.best_score,
+                mov.loss,
+                if mov.exact { " disks" } else { "" },
+                if mov.blunder { "  ??" } else { "" },
+            );
+        }
+
+        for turn in [TurnPlayer::Dark, TurnPlayer::Light] {
+            let blunders: Vec<String> = self
+                .blunders()
+                .filter(|mov| mov.turn == turn)
+                .map(|mov| format!("{}. {}", mov.number, mov.played.to_notation()))
+                .collect();
+            let _ = write!(s, "\n{} blunders: ", turn);
+            match blunders.is_empty() {
+                true => s.push_str("none"),
+                false => s.push_str(&blunders.join(", ")),
+            }
+        }
+
+        s
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("An analysis must be serializable.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::{ggf, RecordedMove};
+
+    /// Plays the first legal move for dark against the search of depth 1.
+    fn weak_dark_game() -> GameRecord {
+        let mut record = GameRecord::default();
+        let mut board = Board::initial();
+        let mut turn = TurnPlayer::Dark;
+
+        loop {
+            let disk = turn.into_disk();
+            let pos = match turn {
+                TurnPlayer::Dark => board.legal_movs(disk).into_iter().next(),
+                TurnPlayer::Light => {
+                    ai::search(&board, disk, 1, RuleSet::Standard).map(|(pos, _)| pos)
+                }
+            };
+            match pos {
+                Some(pos) => {
+                    board.place(pos.clone(), disk).unwrap();
+                    record.moves.push(RecordedMove::new(turn, Some(pos)));
+                }
+                None if !board.exists_legal_mov(TurnPlayer::other(&turn).into_disk()) => break,
+                None => record.moves.push(RecordedMove::new(turn, None)),
+            }
+            turn = TurnPlayer::other(&turn);
+        }
+
+        record
+    }
+
+    #[test]
+    fn test_analyze() {
+        let record = weak_dark_game();
+        let options = AnalysisOptions {
+            depth: 1,
+            ..AnalysisOptions::default()
+        };
+        let analysis = analyze(&record, options).unwrap();
+
+        let played = record.moves.iter().filter(|mov| mov.pos.is_some()).count();
+        assert_eq!(analysis.moves.len(), played);
+        assert!(analysis.moves.iter().all(|mov| mov.loss >= 0));
+        assert!(analysis
+            .moves
+            .iter()
+            .filter(|mov| mov.loss == 0)
+            .all(|mov| mov.best == mov.played && !mov.blunder));
+        assert!(analysis.moves.iter().rev().take(5).all(|mov| mov.exact));
+        assert!(analysis.blunders().any(|mov| mov.turn == TurnPlayer::Dark));
+        assert!(analysis
+            .to_text()
+            .lines()
+            .any(|line| line.ends_with("disks  ??")));
+    }
+
+    #[test]
+    fn test_json() {
+        let record = ggf::parse("(;GM[Othello]B[d3]W[c5];)").unwrap();
+        let analysis = analyze(&record, AnalysisOptions::default()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&analysis.to_json()).unwrap();
+
+        assert_eq!(json["moves"][1]["turn"], "light");
+        assert_eq!(json["moves"][1]["played"], "c5");
+        assert_eq!(json["options"]["depth"], 4);
+    }
+}
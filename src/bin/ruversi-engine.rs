@@ -0,0 +1,29 @@
+use ruversi::core::player::Player;
+use ruversi::player::computer::Computer;
+use ruversi::protocol::engine::Engine;
+
+const DEFAULT_DEPTH: u32 = 4;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let depth = match args.iter().position(|arg| arg == "--depth") {
+        Some(i) => match args.get(i + 1).and_then(|s| s.parse().ok()) {
+            Some(depth) => depth,
+            None => {
+                eprintln!("usage: ruversi-engine [--depth N]");
+                std::process::exit(2);
+            }
+        },
+        None => DEFAULT_DEPTH,
+    };
+
+    let mut engine = Engine::new(Box::new(move || {
+        Box::new(Computer::new(depth)) as Box<dyn Player>
+    }));
+
+    let stdin = std::io::stdin();
+    if let Err(e) = engine.run(stdin.lock(), std::io::stdout()) {
+        eprintln!("ruversi-engine: {}", e);
+        std::process::exit(1);
+    }
+}
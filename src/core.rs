@@ -1,3 +1,6 @@
 pub mod board;
+pub mod clock;
+pub mod game;
 pub mod player;
+pub mod rules;
 pub mod ruversi;
@@ -1,5 +1,10 @@
+#[allow(clippy::module_inception)]
 pub mod board;
 pub mod disk;
+pub mod error;
+pub mod notation;
 
 pub use board::*;
 pub use disk::*;
+pub use error::*;
+pub use notation::{BoardState, ParseError, ParseErrorKind};
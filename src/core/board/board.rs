@@ -1,8 +1,10 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use super::disk::Disk;
+use super::error::{ParseBoardError, PlaceError, PositionError, SizeError};
 
 #[derive(EnumIter)]
 enum Direction {
@@ -31,7 +33,9 @@ impl Direction {
     }
 }
 
-#[derive(Clone)]
+/// Serialized as the notation such as `"d3"`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -44,10 +48,12 @@ impl fmt::Display for Position {
 }
 
 impl Position {
+    /// A position may be on any board up to `Board::MAX_SIZE`, so whether it
+    /// is on a particular board is checked by `Board::contains`.
     pub fn new(x: i32, y: i32) -> Position {
         if !Self::is_valid_range(x, y) {
             panic!(
-                "x and y must be in 0 to 7, but the argument of (x, y) is ({:}, {:})",
+                "x and y must be in 0 to 15, but the argument of (x, y) is ({:}, {:})",
                 x, y
             );
         }
@@ -55,25 +61,88 @@ impl Position {
         Position { x, y }
     }
 
+    /// Like `new`, but returns an error instead of panicking when x or y is
+    /// not in 0 to 15.
+    pub fn try_new(x: i32, y: i32) -> Result<Self, PositionError> {
+        match Self::is_valid_range(x, y) {
+            true => Ok(Position { x, y }),
+            false => Err(PositionError::OutOfRange { x, y }),
+        }
+    }
+
+    /// Returns the position of an index on a board of `size`, from 0 for
+    /// a1 row by row, e.g. 0 to 63 for h8 on the standard board.
+    pub fn from_index(index: usize, size: usize) -> Result<Self, PositionError> {
+        match index < size * size && size <= Board::MAX_SIZE {
+            true => Ok(Position {
+                x: (index % size) as i32,
+                y: (index / size) as i32,
+            }),
+            false => Err(PositionError::IndexOutOfRange { index, size }),
+        }
+    }
+
+    /// The inverse of `from_index`.
+    pub fn index(&self, size: usize) -> usize {
+        self.y as usize * size + self.x as usize
+    }
+
+    /// Iterates over all the squares of a board of `size` from a1, row by
+    /// row.
+    pub fn all(size: usize) -> impl Iterator<Item = Position> {
+        (0..size * size).map(move |i| Position {
+            x: (i % size) as i32,
+            y: (i / size) as i32,
+        })
+    }
+
+    /// Parses a coordinate such as `d3` or `j10`, where the column is `a` to
+    /// `p` and the row is `1` to `16`.
+    pub fn try_from_notation(source: &str) -> Result<Self, PositionError> {
+        let mut chars = source.trim().chars();
+        let col = match chars.next() {
+            Some(col) if col.is_ascii_alphabetic() => col.to_ascii_lowercase(),
+            _ => return Err(PositionError::InvalidNotation(source.to_string())),
+        };
+        let row = chars.as_str();
+        if !(1..=2).contains(&row.len()) || !row.chars().all(|c| c.is_ascii_digit()) {
+            return Err(PositionError::InvalidNotation(source.to_string()));
+        }
+
+        let x = (col as i32) - ('a' as i32);
+        let y = row.parse::<i32>().unwrap_or(0) - 1;
+        Self::try_new(x, y)
+    }
+
+    pub fn to_notation(&self) -> String {
+        format!("{}{}", (b'a' + self.x as u8) as char, self.y + 1)
+    }
+
     fn next(&self, dir: &Direction) -> Option<Self> {
         let (dx, dy) = dir.tuple();
         let x = self.x + dx;
         let y = self.y + dy;
 
-        match Self::is_valid_range(x, y) {
-            true => Some(Self::new(self.x + dx, self.y + dy)),
-            false => None,
-        }
+        Self::try_new(x, y).ok()
     }
 
     fn is_valid_range(x: i32, y: i32) -> bool {
-        (0..8).contains(&x) && (0..8).contains(&y)
+        let range = 0..Board::MAX_SIZE as i32;
+        range.contains(&x) && range.contains(&y)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+/// A square board of an even size from 4 to 16, 8 unless made by
+/// `with_size` or `initial_with_size`.
+///
+/// Serialized as the rows from the top, each of `x`, `o` or `_` for every
+/// square.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
 pub struct Board {
-    disks: [Option<Disk>; 64],
+    size: usize,
+    /// The squares row by row.
+    disks: Vec<Option<Disk>>,
 }
 
 struct BoardLineIter<'a> {
@@ -122,21 +191,27 @@ impl<'a> Iterator for BoardLineIterMut<'a> {
     }
 }
 
+/// Columns are widened to two characters on boards of 10 or more squares a
+/// side, so that the numbers of the header line up.
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "  x 1 2 3 4 5 6 7 8")?;
+        let w = self.label_width();
+        write!(f, "{:w$} x", "")?;
+        for x in 0..self.size {
+            write!(f, " {:>w$}", x + 1)?;
+        }
+        writeln!(f)?;
         writeln!(f, "y\n")?;
-        for y in 0..8 {
-            write!(f, "{}  ", y + 1)?;
-            for x in 0..8 {
-                let index = 8 * y + x;
-                match self.disks[index] {
-                    Some(disk) => write!(f, " {}", disk)?,
-                    None => write!(f, " _")?,
+        for y in 0..self.size {
+            write!(f, "{:<w$}  ", y + 1)?;
+            for x in 0..self.size {
+                match self.disks[self.size * y + x] {
+                    Some(disk) => write!(f, " {:>w$}", disk.to_string())?,
+                    None => write!(f, " {:>w$}", "_")?,
                 }
             }
 
-            if y < 7 {
+            if y + 1 < self.size {
                 writeln!(f)?
             }
         }
@@ -145,32 +220,157 @@ impl fmt::Display for Board {
     }
 }
 
+impl TryFrom<(i32, i32)> for Position {
+    type Error = PositionError;
+
+    fn try_from((x, y): (i32, i32)) -> Result<Self, Self::Error> {
+        Self::try_new(x, y)
+    }
+}
+
+impl TryFrom<String> for Position {
+    type Error = PositionError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::try_from_notation(&source)
+    }
+}
+
+impl From<Position> for String {
+    fn from(pos: Position) -> Self {
+        pos.to_notation()
+    }
+}
+
+impl TryFrom<Vec<String>> for Board {
+    type Error = String;
+
+    fn try_from(rows: Vec<String>) -> Result<Self, Self::Error> {
+        let size = rows.len();
+        Self::with_size(size).map_err(|e| e.to_string())?;
+        if let Some(row) = rows.iter().find(|row| row.chars().count() != size) {
+            return Err(format!("a row must have {} squares: {:?}", size, row));
+        }
+
+        Self::try_from_str(&rows.concat()).map_err(|e| e.to_string())
+    }
+}
+
+impl From<Board> for Vec<String> {
+    fn from(board: Board) -> Self {
+        board
+            .to_compact_str()
+            .as_bytes()
+            .chunks(board.size)
+            .map(|row| String::from_utf8_lossy(row).into_owned())
+            .collect()
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Board {
+    pub const MIN_SIZE: usize = 4;
+    pub const MAX_SIZE: usize = 16;
+    /// The size of the standard board.
+    pub const DEFAULT_SIZE: usize = 8;
+
+    /// Returns an empty standard board.
     pub fn new() -> Self {
-        Self { disks: [None; 64] }
+        Self::empty(Self::DEFAULT_SIZE)
+    }
+
+    /// Returns an empty board of `size` squares a side.
+    pub fn with_size(size: usize) -> Result<Self, SizeError> {
+        match size.is_multiple_of(2) && (Self::MIN_SIZE..=Self::MAX_SIZE).contains(&size) {
+            true => Ok(Self::empty(size)),
+            false => Err(SizeError(size)),
+        }
     }
 
-    pub fn try_from_str(source: &str) -> Result<Self, &'static str> {
-        let mut board = Self::new();
+    fn empty(size: usize) -> Self {
+        Self {
+            size,
+            disks: vec![None; size * size],
+        }
+    }
 
-        for y in 0..8 {
-            for x in 0..8 {
-                let pos = Position::new(x, y);
-                let idx = Self::get_index(&pos);
+    /// Returns the standard starting position.
+    pub fn initial() -> Self {
+        Self::initial_with_size(Self::DEFAULT_SIZE).unwrap()
+    }
 
-                match source.chars().nth(idx) {
-                    Some(c) if c == 'o' => board.set(&pos, Disk::Light),
-                    Some(c) if c == 'x' => board.set(&pos, Disk::Dark),
-                    Some(c) if c == '_' => (),
-                    Some(_) => return Err(r#"character must be 'x', 'o', or '_' "#),
-                    None => return Err("the length of source is not enough"),
-                };
-            }
+    /// Returns the starting position of a board of `size`, with two disks of
+    /// each color crossed in the centre as on the standard board.
+    pub fn initial_with_size(size: usize) -> Result<Self, SizeError> {
+        let mut board = Self::with_size(size)?;
+        let (low, high) = (size as i32 / 2 - 1, size as i32 / 2);
+        board.set(&Position::new(low, low), Disk::Light);
+        board.set(&Position::new(high, high), Disk::Light);
+        board.set(&Position::new(high, low), Disk::Dark);
+        board.set(&Position::new(low, high), Disk::Dark);
+        Ok(board)
+    }
+
+    /// The number of squares a side.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether `pos` is a square of this board.
+    pub fn contains(&self, pos: &Position) -> bool {
+        (pos.x as usize) < self.size && (pos.y as usize) < self.size
+    }
+
+    /// Iterates over all the squares of this board from a1, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        Position::all(self.size)
+    }
+
+    /// The width of the row and column numbers when the board is drawn.
+    pub(crate) fn label_width(&self) -> usize {
+        self.size.to_string().len()
+    }
+
+    /// Parses the squares row by row, whose number has to be the square of
+    /// an even size from 4 to 16, e.g. 64 for the standard board.
+    pub fn try_from_str(source: &str) -> Result<Self, ParseBoardError> {
+        let len = source.chars().count();
+        let size = (Self::MIN_SIZE..=Self::MAX_SIZE)
+            .find(|size| size * size == len)
+            .ok_or(ParseBoardError::InvalidLength(len))?;
+        let mut board = Self::with_size(size).map_err(|_| ParseBoardError::InvalidLength(len))?;
+
+        for (index, c) in source.chars().enumerate() {
+            let pos = Position::from_index(index, size).unwrap();
+            match c {
+                'o' => board.set(&pos, Disk::Light),
+                'x' => board.set(&pos, Disk::Dark),
+                '_' => (),
+                found => return Err(ParseBoardError::InvalidChar { index, found }),
+            };
         }
 
         Ok(board)
     }
 
+    /// Returns a character of `x`, `o` or `_` for every square, the inverse
+    /// of `try_from_str`.
+    pub fn to_compact_str(&self) -> String {
+        self.disks
+            .iter()
+            .map(|disk| match disk {
+                Some(Disk::Light) => 'o',
+                Some(Disk::Dark) => 'x',
+                None => '_',
+            })
+            .collect()
+    }
+
     fn line_iter(&'_ self, pos: Position, dir: Direction) -> BoardLineIter<'_> {
         BoardLineIter::new(self, pos, dir)
     }
@@ -180,89 +380,89 @@ impl Board {
     }
 
     pub fn count_legal_movs(&self, disk: Disk) -> i32 {
-        let mut count = 0;
-        for x in 0..8 {
-            for y in 0..8 {
-                let pos = Position::new(x, y);
-                if self.can_place(pos, disk) {
-                    count += 1;
-                }
-            }
-        }
-        count
+        self.positions()
+            .filter(|pos| self.can_place(pos.clone(), disk))
+            .count() as i32
+    }
+
+    pub fn legal_movs(&self, disk: Disk) -> Vec<Position> {
+        self.positions()
+            .filter(|pos| self.can_place(pos.clone(), disk))
+            .collect()
     }
 
     pub fn exists_legal_mov(&self, disk: Disk) -> bool {
         self.count_legal_movs(disk) > 0
     }
 
-    pub fn count_turn_disks(&self, pos: Position, disk: Disk) -> Result<i32, &'static str> {
+    pub fn count_turn_disks(&self, pos: Position, disk: Disk) -> Result<i32, PlaceError> {
+        if !self.contains(&pos) {
+            return Err(PlaceError::OffBoard(pos));
+        }
         if !self.is_empty(&pos) {
-            return Err("Exists disks already in the position.");
+            return Err(PlaceError::Occupied(pos));
         }
 
-        Ok(Direction::iter()
+        match Direction::iter()
             .map(|dir| {
                 self.count_line_disks_sandwitched_by_another_colors(pos.clone(), dir, &disk)
                     .unwrap_or(0)
             })
-            .sum())
-        .and_then(|c| match c {
-            0 => Err("There is no disk to turn."),
-            _ => Ok(c),
-        })
+            .sum()
+        {
+            0 => Err(PlaceError::NothingToTurn(pos)),
+            c => Ok(c),
+        }
     }
 
+    /// Returns the number of disks turned in the direction, or `None` if the
+    /// line does not end with a disk of `end_disk`.
     fn count_line_disks_sandwitched_by_another_colors(
         &self,
         pos: Position,
         dir: Direction,
         end_disk: &Disk,
-    ) -> Result<i32, &'static str> {
-        if !self.is_empty(&pos) {
-            return Err("Exists disk already.");
-        }
-
+    ) -> Option<i32> {
         let mut iter = self.line_iter(pos, dir);
         match iter.next() {
             Some(disk) if disk != end_disk => {
                 Self::count_line_disks_end_another_color(iter, end_disk).map(|c| c + 1)
             }
-            _ => Err("There is no disk to turn"),
+            _ => None,
         }
     }
 
-    fn count_line_disks_end_another_color(
-        mut iter: BoardLineIter,
-        end_disk: &Disk,
-    ) -> Result<i32, &'static str> {
+    fn count_line_disks_end_another_color(mut iter: BoardLineIter, end_disk: &Disk) -> Option<i32> {
         match iter.next() {
             Some(disk) => {
                 if disk == end_disk {
-                    Ok(0)
+                    Some(0)
                 } else {
                     Self::count_line_disks_end_another_color(iter, end_disk).map(|c| c + 1)
                 }
             }
-            None => Err("The end of this line has not another color."),
+            None => None,
         }
     }
 
-    pub fn turn_disks(&mut self, pos: Position, disk: Disk) -> Result<i32, &'static str> {
+    pub fn turn_disks(&mut self, pos: Position, disk: Disk) -> Result<i32, PlaceError> {
+        if !self.contains(&pos) {
+            return Err(PlaceError::OffBoard(pos));
+        }
         if !self.is_empty(&pos) {
-            return Err("Exists disks already in the position.");
+            return Err(PlaceError::Occupied(pos));
         }
 
-        Ok(Direction::iter()
+        match Direction::iter()
             .map(|dir| {
                 self.turn_line_disks_sandwitched_by_another_colors(pos.clone(), dir, &disk)
                     .unwrap_or(0)
             })
-            .sum())
-        .and_then(|c| match c {
-            0 => Err("There is no disk to turn"),
-            _ => Ok(c),
-        })
+            .sum()
+        {
+            0 => Err(PlaceError::NothingToTurn(pos)),
+            c => Ok(c),
+        }
     }
 
     fn turn_line_disks_sandwitched_by_another_colors(
@@ -270,11 +470,7 @@ impl Board {
         pos: Position,
         dir: Direction,
         end_disk: &Disk,
-    ) -> Result<i32, &'static str> {
-        if !self.is_empty(&pos) {
-            return Err("Exists disk already.");
-        }
-
+    ) -> Option<i32> {
         let mut iter = self.line_iter_mut(pos, dir);
         match iter.next() {
             Some(disk) if disk != end_disk => {
@@ -283,18 +479,18 @@ impl Board {
                     c + 1
                 })
             }
-            _ => Err("There is no disk to turn"),
+            _ => None,
         }
     }
 
     fn turn_line_disks_end_another_color(
         mut iter: BoardLineIterMut,
         end_disk: &Disk,
-    ) -> Result<i32, &'static str> {
+    ) -> Option<i32> {
         match iter.next() {
             Some(disk) => {
                 if disk == end_disk {
-                    Ok(0)
+                    Some(0)
                 } else {
                     Self::turn_line_disks_end_another_color(iter, end_disk).map(|c| {
                         *disk = *end_disk;
@@ -302,45 +498,62 @@ impl Board {
                     })
                 }
             }
-            None => Err("The end of this line has not another color."),
+            None => None,
         }
     }
 
-    fn get_index(pos: &Position) -> usize {
-        (8 * pos.y + pos.x) as usize
+    fn get_index(&self, pos: &Position) -> Option<usize> {
+        self.contains(pos).then(|| pos.index(self.size))
+    }
+
+    /// Panics if `pos` is off the board, like indexing a slice.
+    fn index_on_board(&self, pos: &Position) -> usize {
+        self.get_index(pos).unwrap_or_else(|| {
+            panic!(
+                "{} is off the board of size {}",
+                pos.to_notation(),
+                self.size
+            )
+        })
     }
 
     pub fn is_empty(&self, pos: &Position) -> bool {
         self.get(pos).is_none()
     }
 
+    /// Returns the disk on `pos`, or `None` for an empty square or one off
+    /// the board.
     pub fn get(&self, pos: &Position) -> Option<&Disk> {
-        let idx = Self::get_index(pos);
-        self.disks.get(idx).unwrap().as_ref()
+        let idx = self.get_index(pos)?;
+        self.disks[idx].as_ref()
     }
 
     pub fn get_mut(&mut self, pos: &Position) -> Option<&mut Disk> {
-        let idx = Self::get_index(pos);
-        self.disks.get_mut(idx).unwrap().as_mut()
+        let idx = self.get_index(pos)?;
+        self.disks[idx].as_mut()
     }
 
     pub fn can_place(&self, pos: Position, disk: Disk) -> bool {
         self.count_turn_disks(pos, disk).is_ok()
     }
 
-    pub fn place(&mut self, pos: Position, disk: Disk) -> Result<i32, &'static str> {
-        self.turn_disks(pos.clone(), disk).map(|c| {
-            let idx = Self::get_index(&pos);
+    pub fn place(&mut self, pos: Position, disk: Disk) -> Result<i32, PlaceError> {
+        self.turn_disks(pos.clone(), disk).inspect(|_| {
+            let idx = self.index_on_board(&pos);
             self.disks[idx] = Some(disk);
-            c
         })
     }
 
     pub fn set(&mut self, pos: &Position, disk: Disk) {
-        let index = Self::get_index(pos);
+        let index = self.index_on_board(pos);
         self.disks[index] = Some(disk);
     }
 
+    pub fn remove(&mut self, pos: &Position) {
+        let index = self.index_on_board(pos);
+        self.disks[index] = None;
+    }
+
     pub fn count_disks(&self, disk: &Disk) -> usize {
         self.disks
             .iter()
@@ -349,13 +562,17 @@ impl Board {
     }
 }
 
+/// Builds a board from disks on 0-based `(x, y)` coordinates, e.g.
+/// `board![[(3, 3), Disk::Light], [(4, 3), Disk::Dark]]`. It panics on a
+/// coordinate out of the board like `Position::new`.
 #[macro_export]
 macro_rules! board {
-    ( $( [($x:expr, $y:expr), $disk:expr] ),* ) => {
+    ( $( [($x:expr, $y:expr), $disk:expr] ),* $(,)? ) => {
         {
-            let mut board = Board::new();
+            #[allow(unused_mut)]
+            let mut board = $crate::core::board::Board::new();
             $(
-                board.set(&Position::new($x, $y).unwrap(), $disk);
+                board.set(&$crate::core::board::Position::new($x, $y), $disk);
             )*
             board
         }
@@ -369,7 +586,7 @@ macro_rules! board_fig {
         $(
             source += $line;
         )*
-        Board::try_from_str(&source).unwrap()
+        $crate::core::board::Board::try_from_str(&source).unwrap()
     }}
 }
 
@@ -410,6 +627,30 @@ mod tests {
         assert_eq!(format!("{}", board), board_str);
     }
 
+    #[test]
+    fn test_initial() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "________",
+            "________",
+            "________",
+            "___ox___",
+            "___xo___",
+            "________",
+            "________",
+            "________"
+        );
+
+        assert_eq!(Board::initial(), board);
+    }
+
+    #[test]
+    fn test_compact_str() {
+        let source = "o__o__o__x_x_x____xxx___oxx_xxxo__xxx____x_x_x__o__x__x____o___o";
+        let board = Board::try_from_str(source).unwrap();
+        assert_eq!(board.to_compact_str(), source);
+    }
+
     #[test]
     fn test_count_turn_disks1() {
         #[rustfmt::skip]
@@ -520,6 +761,15 @@ mod tests {
         assert!(board.count_turn_disks(Position::new(3, 4), Light).is_err());
         assert!(board.count_turn_disks(Position::new(3, 5), Light).is_err());
         assert!(board.count_turn_disks(Position::new(3, 6), Light).is_err());
+
+        assert_eq!(
+            board.count_turn_disks(Position::new(3, 2), Dark),
+            Err(PlaceError::NothingToTurn(Position::new(3, 2)))
+        );
+        assert_eq!(
+            board.count_turn_disks(Position::new(3, 3), Dark),
+            Err(PlaceError::Occupied(Position::new(3, 3)))
+        );
     }
 
     #[test]
@@ -843,6 +1093,174 @@ mod tests {
         assert_eq!(board.count_disks(&Light), 8);
     }
 
+    #[test]
+    fn test_position_notation() {
+        assert_eq!(Position::try_from_notation("d3"), Ok(Position::new(3, 2)));
+        assert_eq!(Position::try_from_notation("H8"), Ok(Position::new(7, 7)));
+        assert_eq!(Position::new(0, 0).to_notation(), "a1");
+
+        assert_eq!(Position::try_from_notation("j10"), Ok(Position::new(9, 9)));
+        assert_eq!(Position::new(15, 15).to_notation(), "p16");
+
+        assert!(Position::try_from_notation("q1").is_err());
+        assert!(Position::try_from_notation("a17").is_err());
+        assert!(Position::try_from_notation("a0").is_err());
+        assert!(Position::try_from_notation("a100").is_err());
+        assert!(Position::try_from_notation("").is_err());
+
+        assert_eq!(
+            Position::try_from_notation("q1"),
+            Err(PositionError::OutOfRange { x: 16, y: 0 })
+        );
+        assert_eq!(
+            Position::try_from_notation("a1x"),
+            Err(PositionError::InvalidNotation("a1x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_position_try_new() {
+        assert_eq!(Position::try_new(7, 0), Ok(Position::new(7, 0)));
+        assert_eq!(
+            Position::try_new(-1, 3),
+            Err(PositionError::OutOfRange { x: -1, y: 3 })
+        );
+        assert_eq!(Position::try_from((2, 3)), Ok(Position::new(2, 3)));
+        assert!(Position::try_from((0, 16)).is_err());
+
+        assert_eq!(Position::from_index(10, 8), Ok(Position::new(2, 1)));
+        assert_eq!(Position::new(2, 1).index(8), 10);
+        assert_eq!(Position::from_index(10, 6), Ok(Position::new(4, 1)));
+        assert_eq!(
+            Position::from_index(64, 8),
+            Err(PositionError::IndexOutOfRange { index: 64, size: 8 })
+        );
+
+        let all: Vec<Position> = Position::all(8).collect();
+        assert_eq!(all.len(), 64);
+        assert!(all
+            .iter()
+            .enumerate()
+            .all(|(i, pos)| Position::from_index(i, 8).as_ref() == Ok(pos)));
+        assert_eq!(all[63].to_notation(), "h8");
+    }
+
+    #[test]
+    fn test_board_macro() {
+        let board = crate::board![
+            [(3, 3), Light],
+            [(4, 3), Dark],
+            [(3, 4), Dark],
+            [(4, 4), Light],
+        ];
+
+        assert_eq!(board, Board::initial());
+        assert_eq!(crate::board![], Board::new());
+    }
+
+    #[test]
+    fn test_try_from_str_err() {
+        let initial = Board::initial().to_compact_str();
+
+        assert_eq!(
+            Board::try_from_str(&initial.replacen('o', "?", 1)),
+            Err(ParseBoardError::InvalidChar {
+                index: 27,
+                found: '?'
+            })
+        );
+        assert_eq!(
+            Board::try_from_str(&initial[..60]),
+            Err(ParseBoardError::InvalidLength(60))
+        );
+        assert_eq!(
+            Board::try_from_str(&initial[..60]).unwrap_err().to_string(),
+            "a board must have N*N characters for an even N from 4 to 16, but has 60"
+        );
+        assert_eq!(
+            Board::try_from_str(&"_".repeat(25)),
+            Err(ParseBoardError::InvalidLength(25))
+        );
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(Board::with_size(5), Err(SizeError(5)));
+        assert_eq!(Board::with_size(2), Err(SizeError(2)));
+        assert_eq!(Board::with_size(18), Err(SizeError(18)));
+        assert_eq!(Board::initial_with_size(8), Ok(Board::initial()));
+
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "______",
+            "______",
+            "__ox__",
+            "__xo__",
+            "______",
+            "______"
+        );
+        assert_eq!(Board::initial_with_size(6), Ok(board.clone()));
+        assert_eq!(board.size(), 6);
+        assert!(board.contains(&Position::new(5, 5)));
+        assert!(!board.contains(&Position::new(6, 0)));
+        assert_eq!(board.get(&Position::new(6, 2)), None);
+        assert_eq!(
+            board.legal_movs(Dark),
+            vec![
+                Position::new(2, 1),
+                Position::new(1, 2),
+                Position::new(4, 3),
+                Position::new(3, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_off_board() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "_oox",
+            "____",
+            "____",
+            "____"
+        );
+        assert_eq!(board.clone().place(Position::new(0, 0), Dark), Ok(2));
+        assert_eq!(
+            board.count_turn_disks(Position::new(4, 0), Light),
+            Err(PlaceError::OffBoard(Position::new(4, 0)))
+        );
+    }
+
+    #[test]
+    fn test_large_board() {
+        let mut board = Board::initial_with_size(10).unwrap();
+        assert_eq!(board.get(&Position::new(4, 4)), Some(&Light));
+        assert_eq!(board.get(&Position::new(5, 4)), Some(&Dark));
+        assert_eq!(board.place(Position::new(4, 3), Dark), Ok(1));
+
+        let rows: Vec<String> = board.clone().into();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(Board::try_from(rows), Ok(board.clone()));
+        assert_eq!(
+            Board::try_from_str(&board.to_compact_str()),
+            Ok(board.clone())
+        );
+
+        let shown = board.to_string();
+        assert_eq!(
+            shown.lines().next(),
+            Some("   x  1  2  3  4  5  6  7  8  9 10")
+        );
+        assert_eq!(
+            shown.lines().nth(6),
+            Some("4     _  _  _  _  x  _  _  _  _  _")
+        );
+        assert_eq!(
+            shown.lines().last(),
+            Some("10    _  _  _  _  _  _  _  _  _  _")
+        );
+    }
+
     #[test]
     fn test_count_legal_movs1() {
         let board = Board::new();
@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fmt;
+
+use super::board::Position;
+
+/// Why a disk can not be placed on a square.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PlaceError {
+    /// The square already has a disk.
+    Occupied(Position),
+    /// The disk would not turn any disk of the other color.
+    NothingToTurn(Position),
+    /// The square is not on the board, which is smaller than 16x16.
+    OffBoard(Position),
+}
+
+impl PlaceError {
+    pub fn pos(&self) -> &Position {
+        match self {
+            Self::Occupied(pos) | Self::NothingToTurn(pos) | Self::OffBoard(pos) => pos,
+        }
+    }
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Occupied(pos) => write!(f, "{} already has a disk", pos.to_notation()),
+            Self::NothingToTurn(pos) => {
+                write!(f, "a disk on {} turns no disk", pos.to_notation())
+            }
+            Self::OffBoard(pos) => write!(f, "{} is off the board", pos.to_notation()),
+        }
+    }
+}
+
+impl Error for PlaceError {}
+
+/// Why a string is not a board of `Board::try_from_str`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseBoardError {
+    /// A character other than `x`, `o` and `_` at a 0-based index.
+    InvalidChar { index: usize, found: char },
+    /// The number of characters is not the square of a board size, such as
+    /// 64.
+    InvalidLength(usize),
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { index, found } => write!(
+                f,
+                "character must be 'x', 'o', or '_', but {:?} is at index {}",
+                found, index
+            ),
+            Self::InvalidLength(len) => {
+                write!(
+                    f,
+                    "a board must have N*N characters for an even N from 4 to 16, but has {}",
+                    len
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParseBoardError {}
+
+/// Why a coordinate is not a position on the board.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PositionError {
+    /// The coordinate is not a column and a row like `d3`.
+    InvalidNotation(String),
+    /// The 0-based coordinate is outside of the board.
+    OutOfRange { x: i32, y: i32 },
+    /// The index is not less than the number of squares of a board of
+    /// `size`.
+    IndexOutOfRange { index: usize, size: usize },
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNotation(source) => write!(
+                f,
+                "a coordinate must be a column and a row like d3, but is {:?}",
+                source
+            ),
+            Self::OutOfRange { x, y } => {
+                write!(
+                    f,
+                    "a coordinate must be in a1 to p16, but is ({}, {})",
+                    x, y
+                )
+            }
+            Self::IndexOutOfRange { index, size } => write!(
+                f,
+                "an index must be less than {} on a board of size {}, but is {}",
+                size * size,
+                size,
+                index
+            ),
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+/// Why a board of a size can not be made.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SizeError(pub usize);
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a board size must be even and in 4 to 16, but is {}",
+            self.0
+        )
+    }
+}
+
+impl Error for SizeError {}
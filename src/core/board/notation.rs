@@ -0,0 +1,415 @@
+//! A one-line notation of a position with the side to move, as used by Edax
+//! and the OBF test suites:
+//!
+//! ```text
+//! ---------------------------OX------XO--------------------------- X
+//! ```
+//!
+//! The squares run row by row, 64 from a1 to h8 on the standard board and
+//! N*N on a board of any even size N from 4 to 16, which is told by their
+//! number. `X`, `x`, `*`, `B` and `b` are dark disks, `O`, `o`, `W` and `w`
+//! are light disks, and `-`, `_` and `.` are empty squares. The squares may
+//! be split by whitespace, and the side to move is written after them as a
+//! word of one of the same symbols, optionally followed by `;`.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::core::ruversi::TurnPlayer;
+
+use super::{Board, Disk, Position};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseErrorKind {
+    UnknownSquare(char),
+    /// The number of squares found before the end, which is not that of a
+    /// board.
+    SquareCount(usize),
+    MissingTurn,
+    UnknownTurn(char),
+    TrailingInput,
+    /// A move score of a test suite which is not like `c4:+18`.
+    InvalidScore(String),
+}
+
+/// An error of the notation at a 1-based column of characters, and at a
+/// 1-based line in a test suite.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        write!(f, "column {}: ", self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnknownSquare(c) => write!(f, "unknown square {:?}", c),
+            ParseErrorKind::SquareCount(n) => write!(
+                f,
+                "a board needs N*N squares for an even N from {} to {}, but has {}",
+                Board::MIN_SIZE,
+                Board::MAX_SIZE,
+                n
+            ),
+            ParseErrorKind::MissingTurn => write!(f, "the side to move is missing"),
+            ParseErrorKind::UnknownTurn(c) => write!(f, "unknown side to move {:?}", c),
+            ParseErrorKind::TrailingInput => write!(f, "unexpected input after the position"),
+            ParseErrorKind::InvalidScore(score) => {
+                write!(f, "a move score must be like c4:+18, but is {}", score)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+fn symbol_disk(c: char) -> Option<Option<Disk>> {
+    match c {
+        'X' | 'x' | '*' | 'B' | 'b' => Some(Some(Disk::Dark)),
+        'O' | 'o' | 'W' | 'w' => Some(Some(Disk::Light)),
+        '-' | '_' | '.' => Some(None),
+        _ => None,
+    }
+}
+
+/// A board with the side to move.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BoardState {
+    pub board: Board,
+    pub turn: TurnPlayer,
+}
+
+impl BoardState {
+    pub fn new(board: Board, turn: TurnPlayer) -> Self {
+        Self { board, turn }
+    }
+
+    /// Parses a whole position, such as a line of a test suite without the
+    /// move scores.
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let (state, rest) = Self::parse_prefix(source)?;
+        match rest.trim_start().strip_prefix(';').unwrap_or(rest).trim() {
+            "" => Ok(state),
+            _ => Err(ParseError {
+                line: None,
+                column: source.chars().count() - rest.trim_start().chars().count() + 1,
+                kind: ParseErrorKind::TrailingInput,
+            }),
+        }
+    }
+
+    /// Parses the squares of a board, which may be followed by the side to
+    /// move. Without it, `turn` is to move.
+    pub fn parse_or(source: &str, turn: TurnPlayer) -> Result<Self, ParseError> {
+        match Self::parse(source) {
+            Err(ParseError {
+                line: None,
+                kind: ParseErrorKind::MissingTurn,
+                ..
+            }) => Self::parse(&format!("{} {}", source, Self::turn_symbol(turn))),
+            result => result,
+        }
+    }
+
+    /// Parses a position at the start of `source`, and returns it with the
+    /// rest after the side to move. The side to move is the last word of
+    /// symbols before `;` or anything else, and the size of the board is
+    /// told by the number of symbols before it: N*N squares.
+    pub fn parse_prefix(source: &str) -> Result<(Self, &str), ParseError> {
+        let column = |i: usize| source[..i].chars().count() + 1;
+        let error = |i: usize, kind| ParseError {
+            line: None,
+            column: column(i),
+            kind,
+        };
+        let end = source.find(';').unwrap_or(source.len());
+
+        // The words of symbols with the byte index of each, up to the first
+        // other word if any.
+        let mut words = Vec::new();
+        let mut stop = None;
+        for word in source[..end].split_whitespace() {
+            let i = word.as_ptr() as usize - source.as_ptr() as usize;
+            match word.char_indices().find(|&(_, c)| symbol_disk(c).is_none()) {
+                None => words.push((i, word)),
+                Some((j, c)) => {
+                    stop = Some((i + j, c));
+                    break;
+                }
+            }
+        }
+
+        let turn = match words.last() {
+            Some(&(i, word)) if word.chars().count() == 1 => {
+                words.pop();
+                Some((
+                    i,
+                    word.chars().next().expect("The word must have a symbol."),
+                ))
+            }
+            _ => None,
+        };
+        let squares: Vec<Option<Disk>> = words
+            .iter()
+            .flat_map(|(_, word)| word.chars())
+            .map(|c| symbol_disk(c).expect("The word must have only symbols."))
+            .collect();
+        let size_of = |squares: usize| {
+            (Board::MIN_SIZE..=Board::MAX_SIZE)
+                .step_by(2)
+                .find(|size| size * size == squares)
+        };
+        // A word of more symbols than one after a whole board is a wrong
+        // side to move rather than more squares.
+        if let (None, Some(&(i, word))) = (turn, words.last()) {
+            if size_of(squares.len() - word.chars().count()).is_some() {
+                let (j, c) = word
+                    .char_indices()
+                    .nth(1)
+                    .expect("The word must be longer.");
+                return Err(error(i + j, ParseErrorKind::UnknownTurn(c)));
+            }
+        }
+        let size = size_of(squares.len());
+
+        let (size, (i, c)) = match (size, turn, stop) {
+            (Some(size), Some(turn), _) => (size, turn),
+            (None, Some((i, _)), _) => {
+                return Err(error(i, ParseErrorKind::SquareCount(squares.len())))
+            }
+            (Some(_), None, Some((i, c))) => return Err(error(i, ParseErrorKind::UnknownTurn(c))),
+            (Some(_), None, None) => return Err(error(end, ParseErrorKind::MissingTurn)),
+            (None, None, Some((i, c))) => return Err(error(i, ParseErrorKind::UnknownSquare(c))),
+            (None, None, None) => {
+                return Err(error(end, ParseErrorKind::SquareCount(squares.len())))
+            }
+        };
+
+        let mut board = Board::with_size(size).expect("The size must be valid.");
+        for (index, disk) in squares.into_iter().enumerate() {
+            if let Some(disk) = disk {
+                let pos =
+                    Position::from_index(index, size).expect("The square must be on the board.");
+                board.set(&pos, disk);
+            }
+        }
+        let turn = match symbol_disk(c) {
+            Some(Some(Disk::Dark)) => TurnPlayer::Dark,
+            Some(Some(Disk::Light)) => TurnPlayer::Light,
+            _ => return Err(error(i, ParseErrorKind::UnknownTurn(c))),
+        };
+
+        Ok((Self { board, turn }, &source[i + c.len_utf8()..]))
+    }
+
+    fn turn_symbol(turn: TurnPlayer) -> char {
+        match turn {
+            TurnPlayer::Dark => 'X',
+            TurnPlayer::Light => 'O',
+        }
+    }
+}
+
+/// Writes the position as its squares of `X`, `O` and `-` and the side to
+/// move, e.g. `---...--- X`.
+impl fmt::Display for BoardState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for pos in self.board.positions() {
+            let c = match self.board.get(&pos) {
+                Some(Disk::Dark) => 'X',
+                Some(Disk::Light) => 'O',
+                None => '-',
+            };
+            write!(f, "{}", c)?;
+        }
+        write!(f, " {}", Self::turn_symbol(self.turn))
+    }
+}
+
+/// A position of a test suite with the scores of the moves, if any.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SuiteEntry {
+    pub state: BoardState,
+    pub scores: Vec<(Position, i32)>,
+}
+
+/// Parses a test suite of one position per line, where each position may be
+/// followed by `;`-separated move scores like `c4:+18`. Empty lines and lines
+/// starting with `%` or `#` are skipped. The first error is returned with its
+/// 1-based line number.
+pub fn parse_suite(source: &str) -> Result<Vec<SuiteEntry>, ParseError> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('%') && !line.starts_with('#')
+        })
+        .map(|(i, line)| {
+            parse_suite_line(line).map_err(|e| ParseError {
+                line: Some(i + 1),
+                ..e
+            })
+        })
+        .collect()
+}
+
+fn parse_suite_line(line: &str) -> Result<SuiteEntry, ParseError> {
+    let (state, rest) = BoardState::parse_prefix(line)?;
+    // The column of a part of the line running to its end.
+    let column = |part: &str| line[..line.len() - part.len()].chars().count() + 1;
+    let rest = rest.trim_start();
+    if !rest.is_empty() && !rest.starts_with(';') {
+        return Err(ParseError {
+            line: None,
+            column: column(rest),
+            kind: ParseErrorKind::TrailingInput,
+        });
+    }
+
+    let mut scores = Vec::new();
+    let mut start = 0;
+    for part in rest.split(';') {
+        let score = part.trim();
+        let at = start + part.len() - part.trim_start().len();
+        start += part.len() + 1;
+        if score.is_empty() {
+            continue;
+        }
+
+        let parsed = score.split_once(':').and_then(|(pos, value)| {
+            let pos = Position::try_from_notation(pos).ok()?;
+            let value = value.trim().trim_start_matches('+').parse().ok()?;
+            Some((pos, value))
+        });
+        match parsed {
+            Some(parsed) => scores.push(parsed),
+            None => {
+                return Err(ParseError {
+                    line: None,
+                    column: column(&rest[at..]),
+                    kind: ParseErrorKind::InvalidScore(score.to_string()),
+                })
+            }
+        }
+    }
+
+    Ok(SuiteEntry { state, scores })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INITIAL: &str = "---------------------------OX------XO--------------------------- X";
+
+    #[test]
+    fn test_round_trip() {
+        let state = BoardState::parse(INITIAL).unwrap();
+        assert_eq!(state, BoardState::new(Board::initial(), TurnPlayer::Dark));
+        assert_eq!(state.to_string(), INITIAL);
+    }
+
+    #[test]
+    fn test_other_size() {
+        let state = BoardState::new(Board::initial_with_size(6).unwrap(), TurnPlayer::Light);
+        let source = state.to_string();
+        assert_eq!(source, "--------------OX----XO-------------- O");
+        assert_eq!(BoardState::parse(&source), Ok(state));
+
+        let state = BoardState::new(Board::initial_with_size(16).unwrap(), TurnPlayer::Dark);
+        assert_eq!(BoardState::parse(&state.to_string()), Ok(state));
+    }
+
+    #[test]
+    fn test_tolerant() {
+        let source = "........ ........ ........ ...ox... ...xo... ........ ........ ........ o;";
+        let state = BoardState::parse(source).unwrap();
+        assert_eq!(state.board, Board::initial());
+        assert_eq!(state.turn, TurnPlayer::Light);
+
+        let compact = Board::initial().to_compact_str();
+        assert_eq!(
+            BoardState::parse_or(&compact, TurnPlayer::Light)
+                .unwrap()
+                .turn,
+            TurnPlayer::Light
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| BoardState::parse(source).unwrap_err();
+
+        let e = error(&INITIAL.replacen('O', "?", 1));
+        assert_eq!(e.column, 28);
+        assert_eq!(e.kind, ParseErrorKind::UnknownSquare('?'));
+        assert_eq!(e.to_string(), "column 28: unknown square '?'");
+
+        assert_eq!(error("XO--").kind, ParseErrorKind::SquareCount(4));
+        assert_eq!(
+            error(&"-".repeat(25)).to_string(),
+            "column 26: a board needs N*N squares for an even N from 4 to 16, but has 25"
+        );
+        assert_eq!(error(&INITIAL[..64]).kind, ParseErrorKind::MissingTurn);
+        assert_eq!(
+            error(&INITIAL.replace(" X", " Z")),
+            ParseError {
+                line: None,
+                column: 66,
+                kind: ParseErrorKind::UnknownTurn('Z')
+            }
+        );
+        assert_eq!(
+            error(&format!("{}; O", INITIAL)).kind,
+            ParseErrorKind::TrailingInput
+        );
+        assert_eq!(
+            error(&INITIAL.replace(" X", "X")).kind,
+            ParseErrorKind::SquareCount(65)
+        );
+        assert_eq!(
+            error(&INITIAL.replace(" X", " Xo")),
+            ParseError {
+                line: None,
+                column: 67,
+                kind: ParseErrorKind::UnknownTurn('o')
+            }
+        );
+    }
+
+    #[test]
+    fn test_suite() {
+        let source = format!(
+            "% a comment\n{}; d3:+0; c4:+0;\n\n{}; f5:-2\n",
+            INITIAL, INITIAL
+        );
+        let entries = parse_suite(&source).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].state.board, Board::initial());
+        assert_eq!(entries[0].scores[1], (Position::new(2, 3), 0));
+        assert_eq!(entries[1].scores, vec![(Position::new(5, 4), -2)]);
+
+        let e = parse_suite(&format!("{}\n{} c4:+1\n", INITIAL, INITIAL)).unwrap_err();
+        assert_eq!(
+            (e.line, e.column, e.kind),
+            (Some(2), 68, ParseErrorKind::TrailingInput)
+        );
+        let e = parse_suite("% scores without a board\nX b4:+1\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: a board needs N*N squares for an even N from 4 to 16, but has 0"
+        );
+        let e = parse_suite(&format!("{}; d3:+0; c4=1", INITIAL)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 76: a move score must be like c4:+18, but is c4=1"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A source of time for the game clocks, so that tests can move time on
+/// by hand instead of waiting.
+pub trait Clock {
+    /// The time passed since an arbitrary start.
+    fn now(&self) -> Duration;
+}
+
+/// The wall clock.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock which only moves by `advance`. Clones share the time.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// How much time each player has for the game.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TimeControl {
+    /// The whole game has to be played in `main`.
+    SuddenDeath { main: Duration },
+    /// `increment` is added after every move made in time.
+    Increment { main: Duration, increment: Duration },
+    /// After `main` runs out, every move has to be made in `period`. A move
+    /// taking longer uses up a period for each `period` it takes, and the
+    /// player loses when none is left.
+    ByoYomi {
+        main: Duration,
+        period: Duration,
+        periods: u32,
+    },
+}
+
+impl TimeControl {
+    /// Parses seconds as `MAIN` for sudden death, `MAIN+INCREMENT` or
+    /// `MAIN/PERIODxPERIODS` for byo-yomi, e.g. `300`, `300+5` or
+    /// `300/30x3`. `xPERIODS` may be left out for one period.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let secs = |s: &str| {
+            s.trim()
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .map_err(|_| format!("invalid seconds in time control: {}", s))
+        };
+
+        if let Some((main, increment)) = source.split_once('+') {
+            return Ok(Self::Increment {
+                main: secs(main)?,
+                increment: secs(increment)?,
+            });
+        }
+        if let Some((main, byo_yomi)) = source.split_once('/') {
+            let (period, periods) = match byo_yomi.split_once('x') {
+                Some((period, periods)) => (
+                    period,
+                    periods
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid periods in time control: {}", periods))?,
+                ),
+                None => (byo_yomi, 1),
+            };
+            if periods == 0 {
+                return Err("byo-yomi needs at least one period".to_string());
+            }
+            return Ok(Self::ByoYomi {
+                main: secs(main)?,
+                period: secs(period)?,
+                periods,
+            });
+        }
+        Ok(Self::SuddenDeath {
+            main: secs(source)?,
+        })
+    }
+}
+
+/// The time a player has left.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TimeLeft {
+    pub main: Duration,
+    /// The byo-yomi period, zero without byo-yomi.
+    pub period: Duration,
+    /// The byo-yomi periods left.
+    pub periods: u32,
+}
+
+impl TimeLeft {
+    /// The time the next move can take without losing on time.
+    pub fn for_move(&self) -> Duration {
+        self.main + self.period * self.periods
+    }
+}
+
+/// Writes the time like `4:59`, with the byo-yomi like `0:00 + 3x0:30`.
+impl fmt::Display for TimeLeft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = |d: Duration| format!("{}:{:02}", d.as_secs() / 60, d.as_secs() % 60);
+        write!(f, "{}", minutes(self.main))?;
+        if self.periods > 0 {
+            write!(f, " + {}x{}", self.periods, minutes(self.period))?;
+        }
+        Ok(())
+    }
+}
+
+/// The clock of a player under a time control.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PlayerClock {
+    control: TimeControl,
+    left: TimeLeft,
+}
+
+impl PlayerClock {
+    pub fn new(control: TimeControl) -> Self {
+        let left = match control {
+            TimeControl::SuddenDeath { main } | TimeControl::Increment { main, .. } => TimeLeft {
+                main,
+                period: Duration::ZERO,
+                periods: 0,
+            },
+            TimeControl::ByoYomi {
+                main,
+                period,
+                periods,
+            } => TimeLeft {
+                main,
+                period,
+                periods,
+            },
+        };
+        Self { control, left }
+    }
+
+    pub fn time_left(&self) -> TimeLeft {
+        self.left
+    }
+
+    /// Whether the time has run out.
+    pub fn is_flagged(&self) -> bool {
+        self.left.for_move().is_zero()
+    }
+
+    /// Takes `elapsed` for a move off the clock, and returns whether the
+    /// move was made in time.
+    pub fn charge(&mut self, elapsed: Duration) -> bool {
+        let over = elapsed.saturating_sub(self.left.main);
+        self.left.main = self.left.main.saturating_sub(elapsed);
+
+        match self.control {
+            TimeControl::SuddenDeath { .. } => over.is_zero(),
+            TimeControl::Increment { increment, .. } => {
+                if !over.is_zero() {
+                    return false;
+                }
+                self.left.main += increment;
+                true
+            }
+            TimeControl::ByoYomi { .. } => {
+                let mut over = over;
+                while !over.is_zero() && self.left.periods > 0 {
+                    if over <= self.left.period {
+                        return true;
+                    }
+                    over -= self.left.period;
+                    self.left.periods -= 1;
+                }
+                over.is_zero()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            TimeControl::parse("300"),
+            Ok(TimeControl::SuddenDeath { main: secs(300) })
+        );
+        assert_eq!(
+            TimeControl::parse("60+5"),
+            Ok(TimeControl::Increment {
+                main: secs(60),
+                increment: secs(5)
+            })
+        );
+        assert_eq!(
+            TimeControl::parse("0/30x3"),
+            Ok(TimeControl::ByoYomi {
+                main: secs(0),
+                period: secs(30),
+                periods: 3
+            })
+        );
+        assert!(TimeControl::parse("5m").is_err());
+        assert!(TimeControl::parse("60/10x0").is_err());
+    }
+
+    #[test]
+    fn test_sudden_death_and_increment() {
+        let mut clock = PlayerClock::new(TimeControl::SuddenDeath { main: secs(10) });
+        assert!(clock.charge(secs(4)));
+        assert_eq!(clock.time_left().main, secs(6));
+        assert!(!clock.charge(secs(7)));
+        assert!(clock.is_flagged());
+
+        let mut clock = PlayerClock::new(TimeControl::Increment {
+            main: secs(10),
+            increment: secs(3),
+        });
+        assert!(clock.charge(secs(10)));
+        assert_eq!(clock.time_left().main, secs(3));
+        assert!(!clock.charge(secs(4)));
+    }
+
+    #[test]
+    fn test_byo_yomi() {
+        let mut clock = PlayerClock::new(TimeControl::ByoYomi {
+            main: secs(10),
+            period: secs(5),
+            periods: 3,
+        });
+        assert!(clock.charge(secs(14)));
+        assert_eq!(clock.time_left().periods, 3);
+        assert!(clock.charge(secs(5)));
+        assert!(clock.charge(secs(11)));
+        assert_eq!(clock.time_left().periods, 1);
+        assert_eq!(clock.time_left().to_string(), "0:00 + 1x0:05");
+        assert!(!clock.charge(secs(6)));
+        assert!(clock.is_flagged());
+    }
+}
@@ -0,0 +1,297 @@
+use std::error::Error;
+use std::fmt;
+
+use super::board::*;
+use super::rules::RuleSet;
+use super::ruversi::{GameResult, TurnPlayer};
+
+/// Why a move or a pass is refused by `Game`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameError {
+    /// The game has ended, so no one is to move.
+    Over,
+    /// The side to move can not place a disk there.
+    Illegal(PlaceError),
+    /// The side to move has a legal move, so it can not pass.
+    CannotPass,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Over => write!(f, "the game is over"),
+            Self::Illegal(error) => write!(f, "illegal move: {}", error),
+            Self::CannotPass => write!(f, "a player with a legal move can not pass"),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+impl From<PlaceError> for GameError {
+    fn from(error: PlaceError) -> Self {
+        Self::Illegal(error)
+    }
+}
+
+/// A move or a pass of a player.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ply {
+    pub turn: TurnPlayer,
+    /// `None` for a pass.
+    pub pos: Option<Position>,
+}
+
+/// The rules of a game, advanced one move at a time by the caller.
+///
+/// Nothing here blocks or asks a player, so a game can be driven by an event
+/// loop, a server or a test as well as by `Ruversi::run`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Game {
+    board: Board,
+    turn: TurnPlayer,
+    /// The plies played with the board before each of them.
+    history: Vec<(Ply, Board)>,
+    /// The plies taken back by `undo`, the latest last.
+    undone: Vec<Ply>,
+    rules: RuleSet,
+}
+
+impl Game {
+    pub fn new(board: Board, turn: TurnPlayer) -> Self {
+        Self {
+            board,
+            turn,
+            history: Vec::new(),
+            undone: Vec::new(),
+            rules: RuleSet::Standard,
+        }
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Sets the rules deciding the winner, the standard ones by default.
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+    }
+
+    pub fn current_board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn side_to_move(&self) -> TurnPlayer {
+        self.turn
+    }
+
+    /// The legal moves of the side to move, from a1 row by row.
+    pub fn legal_moves(&self) -> Vec<Position> {
+        self.board.legal_movs(self.turn.into_disk())
+    }
+
+    /// Whether the side to move has no legal move but the game goes on.
+    pub fn must_pass(&self) -> bool {
+        !self.is_over() && !self.board.exists_legal_mov(self.turn.into_disk())
+    }
+
+    /// Places a disk of the side to move and hands the turn to the other
+    /// side. Returns the number of turned disks.
+    pub fn apply_move(&mut self, pos: Position) -> Result<i32, GameError> {
+        if self.is_over() {
+            return Err(GameError::Over);
+        }
+
+        let before = self.board.clone();
+        let turned = self.board.place(pos.clone(), self.turn.into_disk())?;
+        self.push(
+            Ply {
+                turn: self.turn,
+                pos: Some(pos),
+            },
+            before,
+        );
+        self.undone.clear();
+        Ok(turned)
+    }
+
+    /// Hands the turn to the other side when the side to move has no legal
+    /// move.
+    pub fn pass(&mut self) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::Over);
+        }
+        if !self.must_pass() {
+            return Err(GameError::CannotPass);
+        }
+
+        self.push(
+            Ply {
+                turn: self.turn,
+                pos: None,
+            },
+            self.board.clone(),
+        );
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn push(&mut self, ply: Ply, before: Board) {
+        self.history.push((ply, before));
+        self.turn = TurnPlayer::other(&self.turn);
+    }
+
+    /// The plies played so far, the first first.
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Ply> {
+        self.history.iter().map(|(ply, _)| ply)
+    }
+
+    /// Takes back the last ply, which can be played again by `redo` until
+    /// another move or pass is made.
+    pub fn undo(&mut self) -> Option<Ply> {
+        let (ply, before) = self.history.pop()?;
+        self.board = before;
+        self.turn = ply.turn;
+        self.undone.push(ply.clone());
+        Some(ply)
+    }
+
+    /// Plays the last ply taken back by `undo` again.
+    pub fn redo(&mut self) -> Option<Ply> {
+        let ply = self.undone.pop()?;
+        let before = self.board.clone();
+        if let Some(pos) = &ply.pos {
+            self.board
+                .place(pos.clone(), ply.turn.into_disk())
+                .expect("A ply taken back must be legal again.");
+        }
+        self.push(ply.clone(), before);
+        Some(ply)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Whether neither side can place a disk.
+    pub fn is_over(&self) -> bool {
+        !self.board.exists_legal_mov(Disk::Dark) && !self.board.exists_legal_mov(Disk::Light)
+    }
+
+    /// The final counts, or `None` while the game goes on.
+    pub fn result(&self) -> Option<GameResult> {
+        match self.is_over() {
+            true => Some(GameResult::new(
+                self.rules,
+                self.board.count_disks(&Disk::Light),
+                self.board.count_disks(&Disk::Dark),
+            )),
+            false => None,
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::initial(), TurnPlayer::Dark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_fig;
+
+    #[test]
+    fn test_moves() {
+        let mut game = Game::default();
+        assert_eq!(game.legal_moves().len(), 4);
+        assert_eq!(game.result(), None);
+
+        assert_eq!(game.apply_move(Position::new(3, 2)), Ok(1));
+        assert_eq!(game.side_to_move(), TurnPlayer::Light);
+        assert_eq!(game.current_board().count_disks(&Disk::Dark), 4);
+
+        assert_eq!(
+            game.apply_move(Position::new(3, 2)),
+            Err(GameError::Illegal(PlaceError::Occupied(Position::new(
+                3, 2
+            ))))
+        );
+        assert_eq!(game.pass(), Err(GameError::CannotPass));
+        assert_eq!(game.side_to_move(), TurnPlayer::Light);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut game = Game::default();
+        game.apply_move(Position::new(3, 2)).unwrap();
+        game.apply_move(Position::new(2, 4)).unwrap();
+        let played = game.clone();
+
+        assert_eq!(
+            game.undo(),
+            Some(Ply {
+                turn: TurnPlayer::Light,
+                pos: Some(Position::new(2, 4))
+            })
+        );
+        assert_eq!(game.side_to_move(), TurnPlayer::Light);
+        game.undo().unwrap();
+        assert_eq!(game.current_board(), &Board::initial());
+        assert_eq!(game.undo(), None);
+
+        game.redo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(game, played);
+        assert!(!game.can_redo());
+
+        game.undo().unwrap();
+        game.apply_move(Position::new(4, 2)).unwrap();
+        assert!(!game.can_redo());
+        assert_eq!(game.history().count(), 2);
+    }
+
+    #[test]
+    fn test_pass_and_end() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxo",
+            "xxxxxx__"
+        );
+        let mut game = Game::new(board, TurnPlayer::Light);
+
+        assert!(game.must_pass());
+        assert_eq!(game.pass(), Ok(()));
+        assert_eq!(game.legal_moves(), vec![Position::new(7, 7)]);
+
+        game.apply_move(Position::new(7, 7)).unwrap();
+        assert!(game.is_over());
+        assert!(!game.must_pass());
+        assert_eq!(game.pass(), Err(GameError::Over));
+        assert_eq!(
+            game.result(),
+            Some(GameResult::new(RuleSet::Standard, 0, 63))
+        );
+
+        game.set_rules(RuleSet::AntiReversi);
+        assert_eq!(
+            game.result().and_then(|result| result.winner),
+            Some(TurnPlayer::Light)
+        );
+
+        game.undo().unwrap();
+        assert_eq!(game.undo().map(|ply| ply.pos), Some(None));
+        assert!(game.must_pass());
+    }
+}
@@ -1,7 +1,67 @@
 use super::board::*;
+use super::clock::TimeLeft;
+use super::rules::RuleSet;
+use super::ruversi::{GameResult, TurnPlayer};
 
+/// What a player does on its turn.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Action {
+    Move(Position),
+    /// Takes back the moves back to the previous turn of the player.
+    Undo,
+    /// Plays the moves taken back by `Undo` again up to the next turn of
+    /// the player.
+    Redo,
+    /// Gives up the game.
+    Resign,
+    /// Offers a draw, which ends the game if the other player accepts it.
+    OfferDraw,
+    /// Stops the game without a winner.
+    Abort,
+}
+
+/// What a player is given when it has to decide something.
+#[derive(Debug, Clone, Copy)]
+pub struct MoveContext<'a> {
+    pub board: &'a Board,
+    /// The side the player plays.
+    pub side: TurnPlayer,
+    /// The time left of the player at the start of the turn, if the game
+    /// has a time control.
+    pub time_left: Option<TimeLeft>,
+    /// The rules deciding the winner, which a player may aim for.
+    pub rules: RuleSet,
+}
+
+/// A player of a game, told of everything which happens in it.
+///
+/// Only `mov` has to be implemented. The notifications do nothing by
+/// default, so a player which keeps its own board has to follow `on_move`
+/// and `on_undo` as well as `new_game`.
 pub trait Player {
-    fn init(&mut self, board: Board);
-    fn update(&mut self, pos: Position, disk: Disk);
-    fn mov(&self) -> Position;
+    /// Called before a game with the board to start from, the side the
+    /// player plays and the rules deciding the winner.
+    fn new_game(&mut self, _board: &Board, _side: TurnPlayer, _rules: RuleSet) {}
+
+    /// Called after a disk of `turn` is placed on `pos` by either player.
+    fn on_move(&mut self, _pos: &Position, _turn: TurnPlayer) {}
+
+    /// Called after `turn` passes.
+    fn on_pass(&mut self, _turn: TurnPlayer) {}
+
+    /// Called after moves are taken back or played again, with the board to
+    /// continue from.
+    fn on_undo(&mut self, _board: &Board) {}
+
+    /// Called once the game has ended, however it has.
+    fn on_game_end(&mut self, _board: &Board, _result: &GameResult) {}
+
+    /// Chooses an action on the player's turn.
+    fn mov(&mut self, ctx: &MoveContext) -> Action;
+
+    /// Whether to accept a draw offered by the other player. Offers are
+    /// declined by default.
+    fn accept_draw(&mut self, _ctx: &MoveContext) -> bool {
+        false
+    }
 }
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering::*;
+use std::fmt;
+
+use super::ruversi::TurnPlayer;
+
+/// The rules deciding the winner of a game played to the end. Moves are
+/// played the same way under all of them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSet {
+    /// The player with more disks wins.
+    #[default]
+    Standard,
+    /// The player with fewer disks wins, also known as misère.
+    AntiReversi,
+}
+
+impl RuleSet {
+    /// Parses `standard`, or `anti` for anti-reversi, which may also be
+    /// written `anti-reversi` or `misere`.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        match source.to_ascii_lowercase().as_str() {
+            "standard" | "reversi" => Ok(Self::Standard),
+            "anti" | "anti-reversi" | "antireversi" | "misere" => Ok(Self::AntiReversi),
+            _ => Err(format!("unknown rules: {}", source)),
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == Self::Standard
+    }
+
+    /// The winner of a game which has ended with these disks on the board,
+    /// or `None` for a draw.
+    pub fn winner(&self, light_disks: usize, dark_disks: usize) -> Option<TurnPlayer> {
+        let more = match dark_disks.cmp(&light_disks) {
+            Less => TurnPlayer::Light,
+            Greater => TurnPlayer::Dark,
+            Equal => return None,
+        };
+        match self {
+            Self::Standard => Some(more),
+            Self::AntiReversi => Some(TurnPlayer::other(&more)),
+        }
+    }
+
+    /// How good a final disk difference, counted from the view of a player,
+    /// is for that player.
+    pub fn score(&self, disk_diff: i32) -> i32 {
+        match self {
+            Self::Standard => disk_diff,
+            Self::AntiReversi => -disk_diff,
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::AntiReversi => write!(f, "anti-reversi"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_winner() {
+        assert_eq!(RuleSet::Standard.winner(30, 34), Some(TurnPlayer::Dark));
+        assert_eq!(RuleSet::AntiReversi.winner(30, 34), Some(TurnPlayer::Light));
+        assert_eq!(RuleSet::AntiReversi.winner(32, 32), None);
+        assert_eq!(RuleSet::AntiReversi.score(4), -4);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(RuleSet::parse("anti"), Ok(RuleSet::AntiReversi));
+        assert_eq!(RuleSet::parse("Standard"), Ok(RuleSet::Standard));
+        assert!(RuleSet::parse("go").is_err());
+        assert_eq!(
+            RuleSet::parse(&RuleSet::AntiReversi.to_string()),
+            Ok(RuleSet::AntiReversi)
+        );
+    }
+}
@@ -1,27 +1,63 @@
 use super::board::*;
-use super::player::Player;
+use super::clock::{Clock, PlayerClock, SystemClock, TimeControl, TimeLeft};
+use super::game::{Game, GameError};
+use super::player::{Action, MoveContext, Player};
+use super::rules::RuleSet;
 
+use serde::{Deserialize, Serialize};
 use std::{
-    cmp::Ordering::{Equal, Greater, Less},
     fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 pub trait Input {
     fn input_pos(&self) -> Position;
+
+    /// Reads a move or a command such as undo. Only moves are read by
+    /// default.
+    fn input_action(&self) -> Action {
+        Action::Move(self.input_pos())
+    }
+
+    /// Asks whether to accept a draw offered by the other player. Offers
+    /// are declined by default.
+    fn input_accept_draw(&self) -> bool {
+        false
+    }
 }
 
 pub trait IO {
     fn game_start(&self, board: &Board);
     fn skip_turn(&self, turn: &TurnPlayer);
-    fn start_turn(&self, turn: &TurnPlayer);
+    /// Called at the start of each turn, with the time left of the player
+    /// if the game has a time control.
+    fn start_turn(&self, turn: &TurnPlayer, time_left: Option<TimeLeft>);
     fn before_mov(&self, board: &Board, turn: &TurnPlayer);
-    fn after_illegal_mov(&self, pos: &Position, turn: &TurnPlayer);
+    /// Called after an illegal move, with what follows it under the
+    /// `IllegalMovPolicy`.
+    fn after_illegal_mov(&self, error: &PlaceError, turn: &TurnPlayer, retry: Retry);
     fn after_mov(&self, pos: &Position, turn: &TurnPlayer);
     fn after_update(&self, board: &Board);
     fn game_end(&self, board: &Board, result: &GameResult);
+
+    /// Called after `plies` moves and passes are taken back, with the board
+    /// to continue from. It redraws the board by default.
+    fn after_undo(&self, _plies: usize, board: &Board) {
+        self.after_update(board);
+    }
+
+    /// Called after `plies` moves and passes taken back are played again.
+    fn after_redo(&self, _plies: usize, board: &Board) {
+        self.after_update(board);
+    }
+
+    /// Called after `turn` offers a draw, with whether the other player
+    /// accepted it.
+    fn after_draw_offer(&self, _turn: &TurnPlayer, _accepted: bool) {}
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TurnPlayer {
     Dark,
     Light,
@@ -37,18 +73,21 @@ impl fmt::Display for TurnPlayer {
 }
 
 impl TurnPlayer {
-    fn other(turn_player: &Self) -> Self {
+    pub fn other(turn_player: &Self) -> Self {
         match turn_player {
             TurnPlayer::Dark => TurnPlayer::Light,
             TurnPlayer::Light => TurnPlayer::Dark,
         }
     }
 
-    fn reverse(&mut self) {
-        *self = Self::other(self);
+    pub fn from_disk(disk: Disk) -> Self {
+        match disk {
+            Disk::Dark => TurnPlayer::Dark,
+            Disk::Light => TurnPlayer::Light,
+        }
     }
 
-    fn into_disk(self) -> Disk {
+    pub fn into_disk(self) -> Disk {
         match self {
             TurnPlayer::Dark => Disk::Dark,
             TurnPlayer::Light => Disk::Light,
@@ -56,33 +95,214 @@ impl TurnPlayer {
     }
 }
 
+/// How a game has ended.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Neither player could move, and the disks are counted.
+    #[default]
+    Normal,
+    /// The player ran out of time and lost.
+    Timeout(TurnPlayer),
+    /// The player resigned.
+    Resignation(TurnPlayer),
+    /// The player lost by making too many illegal moves.
+    Forfeit(TurnPlayer),
+    /// The players agreed to a draw.
+    DrawAgreed,
+    /// The game was stopped without a winner.
+    Aborted,
+}
+
+impl Outcome {
+    pub fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+
+    /// The player who lost by the outcome rather than by the disks.
+    pub fn loser(&self) -> Option<TurnPlayer> {
+        match self {
+            Self::Timeout(loser) | Self::Resignation(loser) | Self::Forfeit(loser) => Some(*loser),
+            Self::Normal | Self::DrawAgreed | Self::Aborted => None,
+        }
+    }
+}
+
+/// What is done after the last illegal move a player may make in a turn.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Fallback {
+    /// The player loses the game.
+    #[default]
+    Forfeit,
+    /// A random legal move is played for the player.
+    RandomMove,
+}
+
+/// How many illegal moves a player may make in a turn before the
+/// `fallback`. Players are asked again without a limit by default.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct IllegalMovPolicy {
+    /// `None` for no limit.
+    pub max_attempts: Option<u32>,
+    pub fallback: Fallback,
+}
+
+impl IllegalMovPolicy {
+    /// Parses `N` for a forfeit after N illegal moves in a turn, or `N:random`
+    /// for a random move instead, e.g. `3` or `3:random`.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let (max_attempts, fallback) = match source.split_once(':') {
+            Some((max_attempts, "forfeit")) => (max_attempts, Fallback::Forfeit),
+            Some((max_attempts, "random")) => (max_attempts, Fallback::RandomMove),
+            Some((_, fallback)) => return Err(format!("unknown fallback: {}", fallback)),
+            None => (source, Fallback::Forfeit),
+        };
+        let max_attempts = max_attempts
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid number of illegal moves: {}", max_attempts))?;
+
+        Ok(Self {
+            max_attempts: Some(max_attempts),
+            fallback,
+        })
+    }
+
+    /// What follows the `attempts`-th illegal move of a turn.
+    pub fn retry(&self, attempts: u32) -> Retry {
+        match self.max_attempts {
+            None => Retry::Again(None),
+            Some(max_attempts) if attempts < max_attempts => {
+                Retry::Again(Some(max_attempts - attempts))
+            }
+            Some(_) => match self.fallback {
+                Fallback::Forfeit => Retry::Forfeit,
+                Fallback::RandomMove => Retry::RandomMove,
+            },
+        }
+    }
+}
+
+/// What follows an illegal move.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Retry {
+    /// The player is asked again, with the number of illegal moves it may
+    /// still make in the turn if limited.
+    Again(Option<u32>),
+    /// The player has lost.
+    Forfeit,
+    /// A random legal move is played for the player.
+    RandomMove,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, try_from = "GameResultFields")]
 pub struct GameResult {
     pub light_disks: usize,
     pub dark_disks: usize,
     pub winner: Option<TurnPlayer>,
+    /// Left out when serialized if normal.
+    #[serde(skip_serializing_if = "Outcome::is_normal")]
+    pub outcome: Outcome,
+    /// Left out when serialized if standard.
+    #[serde(skip_serializing_if = "RuleSet::is_standard")]
+    pub rules: RuleSet,
 }
 
 impl GameResult {
-    fn new(light_disks: usize, dark_disks: usize) -> Self {
-        let winner = match dark_disks.cmp(&light_disks) {
-            Less => Some(TurnPlayer::Light),
-            Greater => Some(TurnPlayer::Dark),
-            Equal => None,
+    /// A game played to the end, whose winner is decided by `rules`.
+    pub fn new(rules: RuleSet, light_disks: usize, dark_disks: usize) -> Self {
+        Self {
+            light_disks,
+            dark_disks,
+            winner: rules.winner(light_disks, dark_disks),
+            outcome: Outcome::Normal,
+            rules,
+        }
+    }
+
+    /// A game ended by `outcome` with the disks on the board then. Only a
+    /// normal ending is decided by the disks.
+    pub fn with_outcome(
+        outcome: Outcome,
+        rules: RuleSet,
+        light_disks: usize,
+        dark_disks: usize,
+    ) -> Self {
+        let winner = match outcome {
+            Outcome::Normal => return Self::new(rules, light_disks, dark_disks),
+            _ => outcome.loser().map(|loser| TurnPlayer::other(&loser)),
         };
 
         Self {
             light_disks,
             dark_disks,
             winner,
+            outcome,
+            rules,
         }
     }
 }
 
+/// The fields of `GameResult` before checking that they agree.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GameResultFields {
+    light_disks: usize,
+    dark_disks: usize,
+    winner: Option<TurnPlayer>,
+    #[serde(default)]
+    outcome: Outcome,
+    #[serde(default)]
+    rules: RuleSet,
+}
+
+impl TryFrom<GameResultFields> for GameResult {
+    type Error = String;
+
+    fn try_from(fields: GameResultFields) -> Result<Self, Self::Error> {
+        if fields.light_disks + fields.dark_disks > Board::MAX_SIZE * Board::MAX_SIZE {
+            return Err(format!(
+                "{} disks do not fit on the board",
+                fields.light_disks + fields.dark_disks
+            ));
+        }
+
+        let result = Self::with_outcome(
+            fields.outcome,
+            fields.rules,
+            fields.light_disks,
+            fields.dark_disks,
+        );
+        if result.winner != fields.winner {
+            return Err(match fields.outcome {
+                Outcome::Normal => format!(
+                    "the winner {:?} does not agree with {} dark and {} light disks under the {} rules",
+                    fields.winner, fields.dark_disks, fields.light_disks, fields.rules
+                ),
+                outcome => format!(
+                    "the winner {:?} does not agree with the outcome {:?}",
+                    fields.winner, outcome
+                ),
+            });
+        }
+        Ok(result)
+    }
+}
+
 pub struct Ruversi {
-    board: Board,
+    game: Game,
     player_dark: Box<dyn Player>,
     player_light: Box<dyn Player>,
     io: Box<dyn IO>,
+    observers: Vec<Box<dyn IO>>,
+    /// The clocks of dark and light, if the game has a time control.
+    clocks: Option<(PlayerClock, PlayerClock)>,
+    clock: Box<dyn Clock>,
+    illegal_mov_policy: IllegalMovPolicy,
+    /// The state of the generator of random moves.
+    seed: u64,
 }
 
 impl Ruversi {
@@ -93,102 +313,700 @@ impl Ruversi {
         io: Box<dyn IO>,
     ) -> Self {
         Self {
-            board,
+            game: Game::new(board, TurnPlayer::Dark),
             player_dark,
             player_light,
             io,
+            observers: Vec::new(),
+            clocks: None,
+            clock: Box::new(SystemClock::default()),
+            illegal_mov_policy: IllegalMovPolicy::default(),
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(1, |d| d.as_nanos() as u64 | 1),
         }
     }
 
-    fn init_players(&mut self) {
-        self.player_dark.init(self.board.clone());
-        self.player_light.init(self.board.clone());
+    /// Limits the illegal moves a player may make in a turn.
+    pub fn set_illegal_mov_policy(&mut self, policy: IllegalMovPolicy) {
+        self.illegal_mov_policy = policy;
     }
 
-    fn game_start(&self) {
-        self.io.game_start(&self.board);
+    /// Gives both players the time of `control` for the game.
+    pub fn set_time_control(&mut self, control: TimeControl) {
+        self.clocks = Some((PlayerClock::new(control), PlayerClock::new(control)));
     }
 
-    fn ends_game(&self, skip_count: i32) -> bool {
-        // Todo: add a condition of no room to put a disk.
-        skip_count >= 2
+    /// Replaces the wall clock which the time is measured by.
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
     }
 
-    fn turn_player_mov(&mut self, turn: TurnPlayer) -> Position {
-        loop {
-            self.io.before_mov(&self.board, &turn);
-            let pos = self.get_turn_player(turn).mov();
-            if self.board.can_place(pos.clone(), turn.into_disk()) {
-                self.io.after_mov(&pos, &turn);
-                return pos;
-            } else {
-                self.io.after_illegal_mov(&pos, &turn);
-            }
-        }
+    /// The time left of `turn`, if the game has a time control.
+    pub fn time_left(&self, turn: TurnPlayer) -> Option<TimeLeft> {
+        self.clocks.as_ref().map(|(dark, light)| match turn {
+            TurnPlayer::Dark => dark.time_left(),
+            TurnPlayer::Light => light.time_left(),
+        })
     }
 
-    fn start_turn(&self, player: TurnPlayer) {
-        self.io.start_turn(&player);
+    fn player_clock(&mut self, turn: TurnPlayer) -> Option<&mut PlayerClock> {
+        self.clocks.as_mut().map(|(dark, light)| match turn {
+            TurnPlayer::Dark => dark,
+            TurnPlayer::Light => light,
+        })
     }
 
-    fn exists_legal_mov(&self, player: TurnPlayer) -> bool {
-        self.board.exists_legal_mov(player.into_disk())
+    /// Takes the time since `started` off the clock of `turn`, and returns
+    /// whether the player is still in time.
+    fn charge_clock(&mut self, turn: TurnPlayer, started: Duration) -> bool {
+        let elapsed = self.clock.now().saturating_sub(started);
+        self.player_clock(turn)
+            .is_none_or(|clock| clock.charge(elapsed))
     }
 
-    fn update(&mut self, pos: Position, disk: Disk) {
-        self.board
-            .place(pos.clone(), disk)
-            .expect("A disk must be able to place on the pos.");
-        self.player_dark.update(pos.clone(), disk);
-        self.player_light.update(pos, disk);
+    /// Whether `turn` has used more time since `started` than it has.
+    fn is_out_of_time(&self, turn: TurnPlayer, started: Duration) -> bool {
+        let elapsed = self.clock.now().saturating_sub(started);
+        self.time_left(turn)
+            .is_some_and(|time_left| elapsed > time_left.for_move())
+    }
 
-        self.io.after_update(&self.board);
+    /// Sets the player to move first, dark by default.
+    pub fn set_first_turn(&mut self, turn: TurnPlayer) {
+        let rules = self.game.rules();
+        self.game = Game::new(self.game.current_board().clone(), turn);
+        self.game.set_rules(rules);
     }
 
-    fn skip_turn(&self, turn: TurnPlayer) {
-        self.io.skip_turn(&turn);
+    /// Sets the rules deciding the winner, the standard ones by default.
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.game.set_rules(rules);
     }
 
-    fn game_end(&self) {
-        let dark_disks = self.board.count_disks(&Disk::Dark);
-        let light_disks = self.board.count_disks(&Disk::Light);
+    pub fn board(&self) -> &Board {
+        self.game.current_board()
+    }
 
-        let result = GameResult::new(light_disks, dark_disks);
-        self.io.game_end(&self.board, &result);
+    pub fn game(&self) -> &Game {
+        &self.game
     }
 
-    fn get_turn_player(&mut self, turn: TurnPlayer) -> &mut Box<dyn Player> {
-        match turn {
+    /// Attaches a spectator which is told every event after the `IO` given
+    /// to `new`.
+    pub fn add_observer(&mut self, observer: Box<dyn IO>) {
+        self.observers.push(observer);
+    }
+
+    fn notify<F: Fn(&dyn IO)>(&self, f: F) {
+        f(self.io.as_ref());
+        self.observers
+            .iter()
+            .for_each(|observer| f(observer.as_ref()));
+    }
+
+    fn new_game_players(&mut self) {
+        let board = self.game.current_board();
+        let rules = self.game.rules();
+        self.player_dark.new_game(board, TurnPlayer::Dark, rules);
+        self.player_light.new_game(board, TurnPlayer::Light, rules);
+    }
+
+    /// Tells both players of an event.
+    fn tell_players<F: Fn(&mut dyn Player, &Board)>(&mut self, f: F) {
+        let board = self.game.current_board();
+        f(self.player_dark.as_mut(), board);
+        f(self.player_light.as_mut(), board);
+    }
+
+    /// Lets the player of `turn` decide something on the current board.
+    fn ask_player<T, F: FnOnce(&mut dyn Player, &MoveContext) -> T>(
+        &mut self,
+        turn: TurnPlayer,
+        f: F,
+    ) -> T {
+        let ctx = MoveContext {
+            board: self.game.current_board(),
+            side: turn,
+            time_left: self.time_left(turn),
+            rules: self.game.rules(),
+        };
+        let player = match turn {
             TurnPlayer::Dark => &mut self.player_dark,
             TurnPlayer::Light => &mut self.player_light,
+        };
+        f(player.as_mut(), &ctx)
+    }
+
+    fn game_start(&self) {
+        self.notify(|io| io.game_start(self.board()));
+    }
+
+    /// Asks the player to move until the move is legal or the
+    /// `IllegalMovPolicy` gives up on it, and plays it. An undo or a redo is
+    /// done instead if it can be. Returns the outcome if the turn ends the
+    /// game otherwise, as when the player runs out of time or resigns.
+    fn turn_player_mov(&mut self, turn: TurnPlayer) -> Option<Outcome> {
+        let started = self.clock.now();
+        let mut attempts = 0;
+        let mut random_mov = None;
+        loop {
+            let action = match random_mov.take() {
+                Some(pos) => Action::Move(pos),
+                None => {
+                    self.notify(|io| io.before_mov(self.board(), &turn));
+                    self.ask_player(turn, |player, ctx| player.mov(ctx))
+                }
+            };
+            // An action after the time has run out is not taken.
+            if self.is_out_of_time(turn, started) {
+                return self.end_turn(turn, started);
+            }
+
+            let pos = match action {
+                Action::Move(pos) => pos,
+                Action::Undo => match self.undo(turn) {
+                    0 => continue,
+                    _ => return self.end_turn(turn, started),
+                },
+                Action::Redo => match self.redo(turn) {
+                    0 => continue,
+                    _ => return self.end_turn(turn, started),
+                },
+                Action::Resign => {
+                    self.charge_clock(turn, started);
+                    return Some(Outcome::Resignation(turn));
+                }
+                Action::OfferDraw => match self.offer_draw(turn) {
+                    true => {
+                        self.charge_clock(turn, started);
+                        return Some(Outcome::DrawAgreed);
+                    }
+                    false => continue,
+                },
+                Action::Abort => return Some(Outcome::Aborted),
+            };
+            match self.game.apply_move(pos.clone()) {
+                Ok(_) => {
+                    self.charge_clock(turn, started);
+                    self.notify(|io| io.after_mov(&pos, &turn));
+                    self.update(pos, turn);
+                    return None;
+                }
+                Err(GameError::Illegal(error)) => {
+                    attempts += 1;
+                    let retry = self.illegal_mov_policy.retry(attempts);
+                    self.notify(|io| io.after_illegal_mov(&error, &turn, retry));
+                    match retry {
+                        Retry::Again(_) => (),
+                        Retry::Forfeit => {
+                            self.charge_clock(turn, started);
+                            return Some(Outcome::Forfeit(turn));
+                        }
+                        Retry::RandomMove => random_mov = Some(self.random_mov()),
+                    }
+                }
+                Err(error) => panic!("A player must be able to move: {}", error),
+            }
         }
     }
 
-    pub fn run(&mut self) {
-        let mut turn_player = TurnPlayer::Dark;
-        let mut skip_count = 0;
+    /// Takes the time of the turn off the clock of `turn`, and returns the
+    /// loss on time if the player has run out of it.
+    fn end_turn(&mut self, turn: TurnPlayer, started: Duration) -> Option<Outcome> {
+        match self.charge_clock(turn, started) {
+            true => None,
+            false => Some(Outcome::Timeout(turn)),
+        }
+    }
 
-        self.init_players();
-        self.game_start();
+    /// Chooses a legal move of the side to move at random.
+    fn random_mov(&mut self) -> Position {
+        // xorshift64
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
 
-        loop {
-            if self.ends_game(skip_count) {
+        let movs = self.game.legal_moves();
+        movs[(self.seed % movs.len() as u64) as usize].clone()
+    }
+
+    /// Asks the other player whether to accept a draw offered by `turn`.
+    fn offer_draw(&mut self, turn: TurnPlayer) -> bool {
+        let accepted = self.ask_player(TurnPlayer::other(&turn), |player, ctx| {
+            player.accept_draw(ctx)
+        });
+        self.notify(|io| io.after_draw_offer(&turn, accepted));
+        accepted
+    }
+
+    /// Takes back plies until `turn` is to move at an earlier turn, and
+    /// returns how many were taken back; none if there is no such turn.
+    pub fn undo(&mut self, turn: TurnPlayer) -> usize {
+        let Some(plies) = self
+            .game
+            .history()
+            .rev()
+            .position(|ply| ply.turn == turn)
+            .map(|i| i + 1)
+        else {
+            return 0;
+        };
+
+        for _ in 0..plies {
+            self.game.undo();
+        }
+        self.tell_players(|player, board| player.on_undo(board));
+        self.notify(|io| io.after_undo(plies, self.board()));
+        plies
+    }
+
+    /// Plays the plies taken back again until `turn` is to move or none is
+    /// left, and returns how many were played.
+    pub fn redo(&mut self, turn: TurnPlayer) -> usize {
+        let mut plies = 0;
+        while self.game.redo().is_some() {
+            plies += 1;
+            if self.game.side_to_move() == turn {
                 break;
             }
+        }
+        if plies == 0 {
+            return 0;
+        }
+
+        self.tell_players(|player, board| player.on_undo(board));
+        self.notify(|io| io.after_redo(plies, self.board()));
+        plies
+    }
+
+    fn start_turn(&self, player: TurnPlayer) {
+        let time_left = self.time_left(player);
+        self.notify(|io| io.start_turn(&player, time_left));
+    }
+
+    fn update(&mut self, pos: Position, turn: TurnPlayer) {
+        self.tell_players(|player, _| player.on_move(&pos, turn));
+        self.notify(|io| io.after_update(self.board()));
+    }
+
+    fn skip_turn(&self, turn: TurnPlayer) {
+        self.notify(|io| io.skip_turn(&turn));
+    }
+
+    fn game_end(&mut self, result: GameResult) {
+        self.tell_players(|player, board| player.on_game_end(board, &result));
+        self.notify(|io| io.game_end(self.board(), &result));
+    }
+
+    /// Plays the game to the end, and returns the result.
+    pub fn run(&mut self) -> GameResult {
+        self.new_game_players();
+        self.game_start();
+
+        while !self.game.is_over() {
+            let turn = self.game.side_to_move();
+            self.start_turn(turn);
+            if self.game.must_pass() {
+                self.skip_turn(turn);
+                self.game
+                    .pass()
+                    .expect("A player without a move must pass.");
+                self.tell_players(|player, _| player.on_pass(turn));
+            } else if let Some(outcome) = self.turn_player_mov(turn) {
+                let board = self.board();
+                let result = GameResult::with_outcome(
+                    outcome,
+                    self.game.rules(),
+                    board.count_disks(&Disk::Light),
+                    board.count_disks(&Disk::Dark),
+                );
+                self.game_end(result.clone());
+                return result;
+            }
+        }
+
+        // Both players are told that they have no move before the end.
+        let turn = self.game.side_to_move();
+        for turn in [turn, TurnPlayer::other(&turn)] {
+            self.start_turn(turn);
+            self.skip_turn(turn);
+        }
+
+        let result = self.game.result().expect("The game must be over.");
+        self.game_end(result.clone());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::board_fig;
+    use crate::core::clock::ManualClock;
+    use crate::player::computer::Computer;
 
-            self.start_turn(turn_player);
-            if self.exists_legal_mov(turn_player) {
-                let pos = self.turn_player_mov(turn_player);
-                self.update(pos, turn_player.into_disk());
-                skip_count = 0;
-            } else {
-                self.skip_turn(turn_player);
-                skip_count += 1;
+    /// Plays scripted actions, then the first legal move, and records what
+    /// it is told.
+    struct Script {
+        actions: Vec<Action>,
+        accepts_draw: bool,
+        told: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Script {
+        fn new(actions: Vec<Action>) -> Self {
+            Self {
+                actions,
+                accepts_draw: false,
+                told: Rc::new(RefCell::new(Vec::new())),
             }
+        }
+
+        fn tell(&self, event: String) {
+            self.told.borrow_mut().push(event);
+        }
+    }
+
+    impl Player for Script {
+        fn new_game(&mut self, _board: &Board, side: TurnPlayer, _rules: RuleSet) {
+            self.tell(format!("new_game {}", side));
+        }
+
+        fn on_move(&mut self, pos: &Position, turn: TurnPlayer) {
+            self.tell(format!("move {} {}", turn, pos.to_notation()));
+        }
+
+        fn on_pass(&mut self, turn: TurnPlayer) {
+            self.tell(format!("pass {}", turn));
+        }
+
+        fn on_undo(&mut self, _board: &Board) {
+            self.tell("undo".to_string());
+        }
+
+        fn on_game_end(&mut self, _board: &Board, result: &GameResult) {
+            self.tell(format!("end {:?}", result.winner));
+        }
+
+        fn mov(&mut self, ctx: &MoveContext) -> Action {
+            match self.actions.is_empty() {
+                true => Action::Move(ctx.board.legal_movs(ctx.side.into_disk())[0].clone()),
+                false => self.actions.remove(0),
+            }
+        }
+
+        fn accept_draw(&mut self, _ctx: &MoveContext) -> bool {
+            self.accepts_draw
+        }
+    }
+
+    /// Records the numbers of plies taken back and played again, and the
+    /// draw offers.
+    struct History(Rc<RefCell<Vec<String>>>);
 
-            turn_player.reverse();
+    impl IO for History {
+        fn game_start(&self, _board: &Board) {}
+        fn skip_turn(&self, _turn: &TurnPlayer) {}
+        fn start_turn(&self, _turn: &TurnPlayer, _time_left: Option<TimeLeft>) {}
+        fn before_mov(&self, _board: &Board, _turn: &TurnPlayer) {}
+        fn after_illegal_mov(&self, _error: &PlaceError, turn: &TurnPlayer, retry: Retry) {
+            self.0
+                .borrow_mut()
+                .push(format!("illegal {} {:?}", turn, retry));
         }
+        fn after_mov(&self, _pos: &Position, _turn: &TurnPlayer) {}
+        fn after_update(&self, _board: &Board) {}
+        fn game_end(&self, _board: &Board, _result: &GameResult) {}
+
+        fn after_undo(&self, plies: usize, _board: &Board) {
+            self.0.borrow_mut().push(format!("undo {}", plies));
+        }
+
+        fn after_redo(&self, plies: usize, _board: &Board) {
+            self.0.borrow_mut().push(format!("redo {}", plies));
+        }
+
+        fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+            self.0
+                .borrow_mut()
+                .push(format!("draw {} {}", turn, accepted));
+        }
+    }
+
+    fn play(dark: Script, light: Script) -> (Ruversi, GameResult, Vec<String>) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(dark),
+            Box::new(light),
+            Box::new(History(Rc::clone(&events))),
+        );
+        let result = ruversi.run();
+        let events = events.take();
+        (ruversi, result, events)
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let first = Board::initial().legal_movs(Disk::Dark)[0].clone();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let dark = Script::new(vec![
+            // Nothing to take back yet.
+            Action::Undo,
+            Action::Move(first.clone()),
+            Action::Undo,
+            Action::Redo,
+            Action::Undo,
+            Action::Redo,
+            // Nothing to play again after a new move.
+            Action::Undo,
+            Action::Move(first.clone()),
+            Action::Redo,
+        ]);
+        let told = Rc::clone(&dark.told);
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(dark),
+            Box::new(Computer::new(1)),
+            Box::new(History(Rc::clone(&events))),
+        );
+        ruversi.run();
+
+        assert_eq!(
+            *events.borrow(),
+            vec!["undo 2", "redo 2", "undo 2", "redo 2", "undo 2"]
+        );
+        assert_eq!(
+            told.borrow()
+                .iter()
+                .filter(|event| *event == "undo")
+                .count(),
+            5
+        );
+        assert!(ruversi.game().is_over());
+        assert_eq!(ruversi.game().history().next().unwrap().pos, Some(first));
+    }
+
+    #[test]
+    fn test_player_notifications() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxo",
+            "xxxxxx__"
+        );
+        let light = Script::new(Vec::new());
+        let told = Rc::clone(&light.told);
+        let mut ruversi = Ruversi::new(
+            board,
+            Box::new(Script::new(Vec::new())),
+            Box::new(light),
+            Box::new(History(Rc::new(RefCell::new(Vec::new())))),
+        );
+        ruversi.set_first_turn(TurnPlayer::Light);
+        ruversi.run();
+
+        assert_eq!(
+            *told.borrow(),
+            vec![
+                "new_game Light",
+                "pass Light",
+                "move Dark h8",
+                "end Some(Dark)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anti_reversi() {
+        #[rustfmt::skip]
+        let board = board_fig!(
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxx",
+            "xxxxxxxo",
+            "xxxxxx__"
+        );
+        let mut ruversi = Ruversi::new(
+            board,
+            Box::new(Script::new(Vec::new())),
+            Box::new(Script::new(Vec::new())),
+            Box::new(History(Rc::new(RefCell::new(Vec::new())))),
+        );
+        ruversi.set_rules(RuleSet::AntiReversi);
+        ruversi.set_first_turn(TurnPlayer::Light);
+        let result = ruversi.run();
+
+        assert_eq!(result.winner, Some(TurnPlayer::Light));
+        assert_eq!(result.rules, RuleSet::AntiReversi);
+        assert!(result.dark_disks > result.light_disks);
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.ends_with(r#""winner":"light","rules":"anti_reversi"}"#));
+        assert_eq!(serde_json::from_str::<GameResult>(&json).unwrap(), result);
+        let json = json.replace(r#","rules":"anti_reversi""#, "");
+        assert!(serde_json::from_str::<GameResult>(&json).is_err());
+    }
+
+    #[test]
+    fn test_resign() {
+        let dark = Script::new(vec![Action::OfferDraw, Action::Resign]);
+        let (ruversi, result, events) = play(dark, Script::new(Vec::new()));
+
+        assert_eq!(events, vec!["draw Dark false"]);
+        assert_eq!(result.winner, Some(TurnPlayer::Light));
+        assert_eq!(result.outcome, Outcome::Resignation(TurnPlayer::Dark));
+        assert_eq!((result.dark_disks, result.light_disks), (2, 2));
+        assert_eq!(ruversi.game().history().count(), 0);
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.ends_with(r#""outcome":{"resignation":"dark"}}"#));
+        assert_eq!(serde_json::from_str::<GameResult>(&json).unwrap(), result);
+        let json = json.replace(r#""winner":"light""#, r#""winner":null"#);
+        assert!(serde_json::from_str::<GameResult>(&json).is_err());
+    }
+
+    #[test]
+    fn test_draw_agreed_and_abort() {
+        let dark = Script {
+            accepts_draw: true,
+            ..Script::new(Vec::new())
+        };
+        let light = Script::new(vec![Action::OfferDraw]);
+        let (ruversi, result, events) = play(dark, light);
+
+        assert_eq!(events, vec!["draw Light true"]);
+        assert_eq!(result.winner, None);
+        assert_eq!(result.outcome, Outcome::DrawAgreed);
+        assert_eq!(ruversi.game().history().count(), 1);
+
+        let light = Script::new(vec![Action::Abort]);
+        let (_, result, _) = play(Script::new(Vec::new()), light);
+        assert_eq!(result.winner, None);
+        assert_eq!(result.outcome, Outcome::Aborted);
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"light_disks":1,"dark_disks":4,"winner":null,"outcome":"aborted"}"#
+        );
+    }
+
+    #[test]
+    fn test_illegal_mov_policy() {
+        assert_eq!(IllegalMovPolicy::default().retry(100), Retry::Again(None));
+
+        let policy = IllegalMovPolicy::parse("2").unwrap();
+        assert_eq!(policy.retry(1), Retry::Again(Some(1)));
+        assert_eq!(policy.retry(2), Retry::Forfeit);
+        assert_eq!(
+            IllegalMovPolicy::parse("0:random").unwrap().retry(1),
+            Retry::RandomMove
+        );
+        assert!(IllegalMovPolicy::parse("2:pass").is_err());
+        assert!(IllegalMovPolicy::parse("-1").is_err());
+    }
+
+    #[test]
+    fn test_illegal_mov_limit() {
+        let a1 = || Action::Move(Position::new(0, 0));
+        let play_with = |policy: &str| {
+            let events = Rc::new(RefCell::new(Vec::new()));
+            let mut ruversi = Ruversi::new(
+                Board::initial(),
+                Box::new(Script::new(vec![a1(), a1(), a1()])),
+                Box::new(Script::new(Vec::new())),
+                Box::new(History(Rc::clone(&events))),
+            );
+            ruversi.set_illegal_mov_policy(IllegalMovPolicy::parse(policy).unwrap());
+            let result = ruversi.run();
+            let events = events.take();
+            (ruversi, result, events)
+        };
+
+        let (ruversi, result, events) = play_with("2");
+        assert_eq!(
+            events,
+            vec!["illegal Dark Again(Some(1))", "illegal Dark Forfeit"]
+        );
+        assert_eq!(result.winner, Some(TurnPlayer::Light));
+        assert_eq!(result.outcome, Outcome::Forfeit(TurnPlayer::Dark));
+        assert_eq!(ruversi.game().history().count(), 0);
+
+        let (ruversi, result, events) = play_with("1:random");
+        assert_eq!(events[0], "illegal Dark RandomMove");
+        assert_eq!(result.outcome, Outcome::Normal);
+        assert!(ruversi.game().is_over());
+        let first = ruversi.game().history().next().unwrap().pos.clone();
+        assert!(Board::initial()
+            .legal_movs(Disk::Dark)
+            .contains(&first.unwrap()));
+    }
+
+    /// Takes `think` on the clock for every move of the computer.
+    struct Slow {
+        computer: Computer,
+        clock: ManualClock,
+        think: Duration,
+        seen: Rc<RefCell<Vec<Option<TimeLeft>>>>,
+    }
+
+    impl Player for Slow {
+        fn mov(&mut self, ctx: &MoveContext) -> Action {
+            self.seen.borrow_mut().push(ctx.time_left);
+            self.clock.advance(self.think);
+            self.computer.mov(ctx)
+        }
+    }
+
+    #[test]
+    fn test_timeout() {
+        let clock = ManualClock::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let dark = Slow {
+            computer: Computer::new(1),
+            clock: clock.clone(),
+            think: Duration::from_secs(4),
+            seen: Rc::clone(&seen),
+        };
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(dark),
+            Box::new(Computer::new(1)),
+            Box::new(History(Rc::new(RefCell::new(Vec::new())))),
+        );
+        ruversi.set_time_control(TimeControl::SuddenDeath {
+            main: Duration::from_secs(10),
+        });
+        ruversi.set_clock(Box::new(clock));
+        let result = ruversi.run();
+
+        let mains: Vec<u64> = seen
+            .borrow()
+            .iter()
+            .map(|time_left| time_left.unwrap().main.as_secs())
+            .collect();
+        assert_eq!(mains, vec![10, 6, 2]);
+        assert_eq!(result.winner, Some(TurnPlayer::Light));
+        assert_eq!(result.outcome, Outcome::Timeout(TurnPlayer::Dark));
+        assert_eq!(ruversi.game().history().count(), 4);
+        assert_eq!(
+            ruversi.time_left(TurnPlayer::Light).unwrap().main.as_secs(),
+            10
+        );
 
-        self.game_end();
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.ends_with(r#""outcome":{"timeout":"dark"}}"#));
+        assert_eq!(serde_json::from_str::<GameResult>(&json).unwrap(), result);
     }
 }
@@ -1 +1,6 @@
+pub mod color_cui;
 pub mod cui;
+pub mod json_log;
+pub mod multi;
+pub mod tui;
+pub mod web;
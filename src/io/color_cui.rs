@@ -0,0 +1,283 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+
+use crate::core::board::{Board, Disk, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::player::Action;
+use crate::core::ruversi::{GameResult, Input, Retry, TurnPlayer, IO};
+
+use super::cui::CUI;
+
+const RESET: &str = "\x1b[0m";
+const BOARD_BG: &str = "\x1b[42m";
+const LAST_MOV_BG: &str = "\x1b[43m";
+const FLIPPED_BG: &str = "\x1b[46m";
+const DARK_FG: &str = "\x1b[30m";
+const LIGHT_FG: &str = "\x1b[97m";
+const EMPTY_FG: &str = "\x1b[32m";
+
+/// A CUI which draws the board with Unicode disks and, unless disabled,
+/// ANSI colors. The last move is highlighted on every redraw.
+#[derive(Clone)]
+pub struct ColorCUI {
+    color: bool,
+    last_mov: RefCell<Option<Position>>,
+    /// Reads the input, knowing the size of the board.
+    cui: CUI,
+}
+
+impl Default for ColorCUI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorCUI {
+    /// Colors are enabled unless the `NO_COLOR` environment variable is set.
+    pub fn new() -> Self {
+        Self::with_color(!Self::no_color_requested())
+    }
+
+    pub fn with_color(color: bool) -> Self {
+        Self {
+            color,
+            last_mov: RefCell::new(None),
+            cui: CUI::new(),
+        }
+    }
+
+    fn no_color_requested() -> bool {
+        std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+    }
+
+    fn get_mark(turn: &TurnPlayer) -> &'static str {
+        match turn {
+            TurnPlayer::Dark => "●",
+            TurnPlayer::Light => "○",
+        }
+    }
+
+    fn disk_mark(&self, disk: Option<&Disk>) -> &str {
+        match (disk, self.color) {
+            (Some(_), true) => "●",
+            (Some(Disk::Dark), false) => "●",
+            (Some(Disk::Light), false) => "○",
+            (None, true) => "·",
+            (None, false) => "_",
+        }
+    }
+
+    fn disk_fg(disk: Option<&Disk>) -> &str {
+        match disk {
+            Some(Disk::Dark) => DARK_FG,
+            Some(Disk::Light) => LIGHT_FG,
+            None => EMPTY_FG,
+        }
+    }
+
+    pub fn render(&self, board: &Board) -> String {
+        self.render_highlighted(board, self.last_mov.borrow().as_ref(), &[])
+    }
+
+    /// Renders the board with `last_mov` and the `flipped` disks highlighted,
+    /// marked with `*` and `+` when colors are disabled.
+    pub fn render_highlighted(
+        &self,
+        board: &Board,
+        last_mov: Option<&Position>,
+        flipped: &[Position],
+    ) -> String {
+        let mut s = String::new();
+        let size = board.size() as i32;
+        let w = board.label_width();
+        // The extra width of a column on boards of 10 or more squares a side.
+        let pad = " ".repeat(w - 1);
+
+        let _ = write!(s, "{:w$} x", "");
+        for x in 0..size {
+            let _ = write!(s, " {:>w$}", x + 1);
+        }
+        s.push_str("\ny\n\n");
+        for y in 0..size {
+            let _ = write!(s, "{:<w$}  ", y + 1);
+            for x in 0..size {
+                let pos = Position::new(x, y);
+                let disk = board.get(&pos);
+                let is_last = last_mov == Some(&pos);
+                let is_flipped = flipped.contains(&pos);
+                let mark = self.disk_mark(disk);
+
+                if self.color {
+                    let bg = match (is_last, is_flipped) {
+                        (true, _) => LAST_MOV_BG,
+                        (false, true) => FLIPPED_BG,
+                        (false, false) => BOARD_BG,
+                    };
+                    let _ = write!(s, "{}{} {}{}", bg, pad, Self::disk_fg(disk), mark);
+                } else {
+                    let prefix = match (is_last, is_flipped) {
+                        (true, _) => '*',
+                        (false, true) => '+',
+                        (false, false) => ' ',
+                    };
+                    let _ = write!(s, "{}{}{}", pad, prefix, mark);
+                }
+            }
+
+            if self.color {
+                let _ = write!(s, "{} {}", BOARD_BG, RESET);
+            }
+            if y + 1 < size {
+                s.push('\n');
+            }
+        }
+
+        s
+    }
+}
+
+impl IO for ColorCUI {
+    fn game_start(&self, board: &Board) {
+        self.last_mov.replace(None);
+        self.cui.set_size(board);
+        println!("Ruversi");
+        println!("===== Game Start =====\n");
+        println!("{}\n", self.render(board));
+    }
+
+    fn skip_turn(&self, turn: &TurnPlayer) {
+        println!("There is no place to a {} disk.", Self::get_mark(turn));
+    }
+
+    fn start_turn(&self, turn: &TurnPlayer, time_left: Option<TimeLeft>) {
+        match time_left {
+            Some(time_left) => println!("\n{}'s turn: ({} left)", Self::get_mark(turn), time_left),
+            None => println!("\n{}'s turn:", Self::get_mark(turn)),
+        }
+    }
+
+    fn before_mov(&self, board: &Board, _turn: &TurnPlayer) {
+        self.cui.set_size(board);
+    }
+
+    fn after_illegal_mov(&self, error: &PlaceError, _turn: &TurnPlayer, retry: Retry) {
+        CUI::print_illegal_mov(error, retry);
+    }
+
+    fn after_mov(&self, pos: &Position, _turn: &TurnPlayer) {
+        self.last_mov.replace(Some(pos.clone()));
+    }
+
+    fn after_update(&self, board: &Board) {
+        println!("\n\n{}\n", self.render(board));
+    }
+
+    fn game_end(&self, board: &Board, result: &GameResult) {
+        println!("\n\n===== Result =====");
+        println!("\n{}\n", self.render(board));
+        println!("● vs ○");
+        println!("{} : {}", result.dark_disks, result.light_disks);
+        CUI::print_result(result, Self::get_mark);
+    }
+
+    fn after_undo(&self, plies: usize, board: &Board) {
+        self.last_mov.replace(None);
+        CUI::print_history_change(plies, true);
+        println!("\n{}\n", self.render(board));
+    }
+
+    fn after_redo(&self, plies: usize, board: &Board) {
+        self.last_mov.replace(None);
+        CUI::print_history_change(plies, false);
+        println!("\n{}\n", self.render(board));
+    }
+
+    fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+        if !accepted {
+            println!(
+                "\n{} offered a draw, which was declined.",
+                Self::get_mark(turn)
+            );
+        }
+    }
+}
+
+impl Input for ColorCUI {
+    fn input_pos(&self) -> Position {
+        self.cui.input_pos()
+    }
+
+    fn input_action(&self) -> Action {
+        self.cui.input_action()
+    }
+
+    fn input_accept_draw(&self) -> bool {
+        self.cui.input_accept_draw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_fig;
+
+    #[rustfmt::skip]
+    fn board() -> Board {
+        board_fig!(
+            "________",
+            "________",
+            "___x____",
+            "___xx___",
+            "___xo___",
+            "________",
+            "________",
+            "________"
+        )
+    }
+
+    #[test]
+    fn test_render_without_color() {
+        let cui = ColorCUI::with_color(false);
+        cui.after_mov(&Position::new(3, 2), &TurnPlayer::Dark);
+
+        let rendered = cui.render(&board());
+        assert!(!rendered.contains('\x1b'));
+        assert_eq!(rendered.lines().nth(5), Some("3   _ _ _*● _ _ _ _"));
+        assert_eq!(rendered.lines().nth(7), Some("5   _ _ _ ● ○ _ _ _"));
+    }
+
+    #[test]
+    fn test_render_other_sizes() {
+        let cui = ColorCUI::with_color(false);
+
+        let rendered = cui.render(&Board::initial_with_size(6).unwrap());
+        assert_eq!(rendered.lines().next(), Some("  x 1 2 3 4 5 6"));
+        assert_eq!(rendered.lines().nth(5), Some("3   _ _ ○ ● _ _"));
+        assert_eq!(rendered.lines().count(), 9);
+
+        let rendered = cui.render(&Board::initial_with_size(10).unwrap());
+        assert_eq!(
+            rendered.lines().next(),
+            Some("   x  1  2  3  4  5  6  7  8  9 10")
+        );
+        assert_eq!(
+            rendered.lines().nth(8),
+            Some("6     _  _  _  _  ●  ○  _  _  _  _")
+        );
+        assert_eq!(
+            rendered.lines().last(),
+            Some("10    _  _  _  _  _  _  _  _  _  _")
+        );
+    }
+
+    #[test]
+    fn test_render_with_color() {
+        let cui = ColorCUI::with_color(true);
+        cui.after_mov(&Position::new(3, 2), &TurnPlayer::Dark);
+
+        let rendered = cui.render(&board());
+        assert!(rendered.contains(BOARD_BG));
+        assert_eq!(rendered.matches(LAST_MOV_BG).count(), 1);
+        assert!(rendered.lines().all(|line| !line.contains('○')));
+    }
+}
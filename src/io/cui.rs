@@ -1,29 +1,53 @@
+use std::cell::Cell;
 use std::io::Write;
+use std::rc::Rc;
 
-use crate::core::board::{Board, Position};
-use crate::core::ruversi::{GameResult, Input, TurnPlayer, IO};
+use crate::core::board::{Board, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::player::Action;
+use crate::core::ruversi::{GameResult, Input, Outcome, Retry, TurnPlayer, IO};
 
+/// Clones share the size of the board last shown, which bounds the
+/// coordinates read as input.
 #[derive(Clone)]
-pub struct CUI;
+pub struct CUI {
+    size: Rc<Cell<usize>>,
+}
+
+impl Default for CUI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl CUI {
     pub fn new() -> Self {
-        Self
+        Self {
+            size: Rc::new(Cell::new(Board::DEFAULT_SIZE)),
+        }
+    }
+
+    pub(crate) fn set_size(&self, board: &Board) {
+        self.size.set(board.size());
+    }
+
+    fn is_valid_num(&self, num: i32) -> bool {
+        (1..=self.size.get() as i32).contains(&num)
     }
 
-    fn get_mark(turn: &TurnPlayer) -> &str {
+    fn get_mark(turn: &TurnPlayer) -> &'static str {
         match turn {
             TurnPlayer::Dark => "x",
             TurnPlayer::Light => "o",
         }
     }
 
-    fn input_num(prompt: &str) -> i32 {
+    fn input_num(&self, prompt: &str) -> i32 {
         loop {
             print!("{}", prompt);
-            std::io::stdout().flush();
+            let _ = std::io::stdout().flush();
             match Self::read_num() {
-                Ok(num) if (1..=8).contains(&num) => return num,
+                Ok(num) if self.is_valid_num(num) => return num,
                 Ok(num) => println!("{} is not valid.", num),
                 Err(msg) => println!("{}", msg),
             }
@@ -31,17 +55,110 @@ impl CUI {
     }
 
     fn read_num() -> Result<i32, String> {
+        Self::read_line()?.parse::<i32>().map_err(|e| e.to_string())
+    }
+
+    fn read_line() -> Result<String, String> {
         let mut s = String::new();
         std::io::stdin()
             .read_line(&mut s)
             .map_err(|_| "read_line error")?;
         s.retain(|c| c != '\n');
-        s.parse::<i32>().map_err(|e| e.to_string())
+        Ok(s)
+    }
+
+    /// Reads x like `input_num`, or a command: `u` for undo, `r` for redo,
+    /// `resign`, `draw` to offer a draw, or `abort`.
+    fn input_x_or_command(&self) -> Result<i32, Action> {
+        loop {
+            print!("input x (u: undo, r: redo, resign, draw, abort) >> ");
+            let _ = std::io::stdout().flush();
+            let line = match Self::read_line() {
+                Ok(line) => line,
+                Err(msg) => {
+                    println!("{}", msg);
+                    continue;
+                }
+            };
+            match line.trim() {
+                "u" => return Err(Action::Undo),
+                "r" => return Err(Action::Redo),
+                "resign" => return Err(Action::Resign),
+                "draw" => return Err(Action::OfferDraw),
+                "abort" => return Err(Action::Abort),
+                s => match s.parse::<i32>() {
+                    Ok(num) if self.is_valid_num(num) => return Ok(num),
+                    Ok(num) => println!("{} is not valid.", num),
+                    Err(e) => println!("{}", e),
+                },
+            }
+        }
+    }
+
+    pub(crate) fn print_history_change(plies: usize, taken_back: bool) {
+        let verb = if taken_back {
+            "taken back"
+        } else {
+            "played again"
+        };
+        match plies {
+            1 => println!("\n1 move is {}.", verb),
+            _ => println!("\n{} moves are {}.", plies, verb),
+        }
+    }
+
+    /// Prints why a move is illegal and what follows it.
+    pub(crate) fn print_illegal_mov(error: &PlaceError, retry: Retry) {
+        let pos = error.pos();
+        match error {
+            PlaceError::Occupied(_) => {
+                println!(
+                    "\nA disk already exists on ({}, {}). ",
+                    pos.x + 1,
+                    pos.y + 1
+                )
+            }
+            PlaceError::NothingToTurn(_) => println!(
+                "\nA disk on ({}, {}) turns no disk of the other color. ",
+                pos.x + 1,
+                pos.y + 1
+            ),
+            PlaceError::OffBoard(_) => {
+                println!("\n({}, {}) is off the board. ", pos.x + 1, pos.y + 1)
+            }
+        }
+        match retry {
+            Retry::Again(None) => println!("Please input once again\n"),
+            Retry::Again(Some(1)) => println!("Please input once again (1 attempt left)\n"),
+            Retry::Again(Some(left)) => {
+                println!("Please input once again ({} attempts left)\n", left)
+            }
+            Retry::Forfeit => println!("Too many illegal moves.\n"),
+            Retry::RandomMove => println!("Too many illegal moves. A random move is played.\n"),
+        }
+    }
+
+    /// Prints the winner and how the game has ended, with the players
+    /// shown by `mark`.
+    pub(crate) fn print_result(result: &GameResult, mark: fn(&TurnPlayer) -> &'static str) {
+        match (result.winner, result.outcome) {
+            (_, Outcome::Aborted) => println!("\nAborted"),
+            (None, _) => println!("\nDraw"),
+            (Some(player), _) => println!("\n{} WIN", mark(&player)),
+        }
+        match result.outcome {
+            Outcome::Normal | Outcome::Aborted => (),
+            Outcome::Timeout(loser) => println!("{} ran out of time.", mark(&loser)),
+            Outcome::Resignation(loser) => println!("{} resigned.", mark(&loser)),
+            Outcome::Forfeit(loser) => println!("{} forfeited by illegal moves.", mark(&loser)),
+            Outcome::DrawAgreed => println!("The draw was agreed."),
+        }
     }
 }
 
 impl IO for CUI {
     fn game_start(&self, board: &Board) {
+        self.set_size(board);
         println!("Ruversi");
         println!("===== Game Start =====\n");
         println!("{}\n", board);
@@ -51,21 +168,19 @@ impl IO for CUI {
         println!("There is no place to a {} disk.", Self::get_mark(turn));
     }
 
-    fn start_turn(&self, turn: &TurnPlayer) {
-        println!("\n{}'s turn:", Self::get_mark(turn));
+    fn start_turn(&self, turn: &TurnPlayer, time_left: Option<TimeLeft>) {
+        match time_left {
+            Some(time_left) => println!("\n{}'s turn: ({} left)", Self::get_mark(turn), time_left),
+            None => println!("\n{}'s turn:", Self::get_mark(turn)),
+        }
     }
 
-    fn before_mov(&self, _board: &Board, _turn: &TurnPlayer) {
-        //println!("\ninput move");
+    fn before_mov(&self, board: &Board, _turn: &TurnPlayer) {
+        self.set_size(board);
     }
 
-    fn after_illegal_mov(&self, pos: &Position, _turn: &TurnPlayer) {
-        println!(
-            "\nA disk cannot be placed on ({}, {}). ",
-            pos.x + 1,
-            pos.y + 1
-        );
-        println!("Please input once again\n");
+    fn after_illegal_mov(&self, error: &PlaceError, _turn: &TurnPlayer, retry: Retry) {
+        Self::print_illegal_mov(error, retry);
     }
 
     fn after_mov(&self, _pos: &Position, _turn: &TurnPlayer) {}
@@ -79,18 +194,53 @@ impl IO for CUI {
         println!("\n{}\n", board);
         println!("x vs o");
         println!("{} : {}", result.dark_disks, result.light_disks);
+        Self::print_result(result, Self::get_mark);
+    }
+
+    fn after_undo(&self, plies: usize, board: &Board) {
+        Self::print_history_change(plies, true);
+        println!("\n{}\n", board);
+    }
 
-        match result.winner {
-            None => println!("\nDraw"),
-            Some(player) => println!("\n{} WIN", Self::get_mark(&player)),
+    fn after_redo(&self, plies: usize, board: &Board) {
+        Self::print_history_change(plies, false);
+        println!("\n{}\n", board);
+    }
+
+    fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+        if !accepted {
+            println!(
+                "\n{} offered a draw, which was declined.",
+                Self::get_mark(turn)
+            );
         }
     }
 }
 
 impl Input for CUI {
     fn input_pos(&self) -> Position {
-        let x = Self::input_num("input x >> ") - 1;
-        let y = Self::input_num("input y >> ") - 1;
+        let x = self.input_num("input x >> ") - 1;
+        let y = self.input_num("input y >> ") - 1;
         Position::new(x, y)
     }
+
+    fn input_action(&self) -> Action {
+        match self.input_x_or_command() {
+            Ok(x) => Action::Move(Position::new(x - 1, self.input_num("input y >> ") - 1)),
+            Err(action) => action,
+        }
+    }
+
+    fn input_accept_draw(&self) -> bool {
+        loop {
+            print!("\nA draw is offered. Accept? (y/n) >> ");
+            let _ = std::io::stdout().flush();
+            match Self::read_line().map(|line| line.trim().to_ascii_lowercase()) {
+                Ok(answer) if answer == "y" || answer == "yes" => return true,
+                Ok(answer) if answer == "n" || answer == "no" => return false,
+                Ok(answer) => println!("{} is not valid.", answer),
+                Err(msg) => println!("{}", msg),
+            }
+        }
+    }
 }
@@ -0,0 +1,508 @@
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Board, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::{GameResult, Outcome, Retry, TurnPlayer, IO};
+use crate::record::{Ending, GameRecord, RecordResult, RecordedMove};
+
+/// An event of a game as logged by `JsonLog`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Event {
+    GameStart {
+        board: Board,
+        /// Left out when standard.
+        #[serde(default, skip_serializing_if = "RuleSet::is_standard")]
+        rules: RuleSet,
+    },
+    StartTurn {
+        turn: TurnPlayer,
+    },
+    SkipTurn {
+        turn: TurnPlayer,
+    },
+    IllegalMov {
+        pos: Position,
+        turn: TurnPlayer,
+        reason: IllegalReason,
+        /// e.g. `{"again":2}`, `{"again":null}` without a limit, `"forfeit"`
+        /// or `"random_move"`.
+        retry: Retry,
+    },
+    Mov {
+        pos: Position,
+        turn: TurnPlayer,
+    },
+    Update {
+        board: Board,
+    },
+    GameEnd {
+        board: Board,
+        result: GameResult,
+    },
+    Undo {
+        plies: usize,
+        board: Board,
+    },
+    Redo {
+        plies: usize,
+        board: Board,
+    },
+    DrawOffer {
+        turn: TurnPlayer,
+        accepted: bool,
+    },
+}
+
+/// Why a move was illegal, the kind of `PlaceError`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IllegalReason {
+    Occupied,
+    NothingToTurn,
+    OffBoard,
+}
+
+impl From<&PlaceError> for IllegalReason {
+    fn from(error: &PlaceError) -> Self {
+        match error {
+            PlaceError::Occupied(_) => Self::Occupied,
+            PlaceError::NothingToTurn(_) => Self::NothingToTurn,
+            PlaceError::OffBoard(_) => Self::OffBoard,
+        }
+    }
+}
+
+/// A line of the log: the event with the milliseconds since the Unix epoch,
+/// e.g. `{"time":1700000000000,"type":"mov","pos":"d3","turn":"dark"}`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// Writes every event of a game as a JSON line, so games run without a
+/// player at the terminal can be inspected and replayed later. Each line is
+/// flushed as soon as it is written.
+pub struct JsonLog<W: Write> {
+    writer: RefCell<W>,
+    failed: Cell<bool>,
+    rules: RuleSet,
+}
+
+impl JsonLog<BufWriter<File>> {
+    /// Creates the file at `path`, truncating it if it exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        File::create(path).map(|file| Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> JsonLog<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: RefCell::new(writer),
+            failed: Cell::new(false),
+            rules: RuleSet::Standard,
+        }
+    }
+
+    /// Logs `rules` as the rules of the game, which are standard by default.
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    fn log(&self, event: Event) {
+        let entry = Entry {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+            event,
+        };
+        let line = serde_json::to_string(&entry).expect("An event must be serializable.");
+
+        let mut writer = self.writer.borrow_mut();
+        let result = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+        // Reports only the first failure instead of one per event.
+        if let Err(e) = result {
+            if !self.failed.replace(true) {
+                eprintln!("cannot write the game log: {}", e);
+            }
+        }
+    }
+}
+
+/// Reads the entries of a log written by `JsonLog`, skipping empty lines.
+pub fn read_entries<R: BufRead>(reader: R) -> Result<Vec<Entry>, String> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line.map_err(|e| e.to_string())?;
+            serde_json::from_str(&line).map_err(|e| format!("line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Rebuilds the game record of a log, with the thinking time of each move
+/// from the start of the turn. The passes before the end of the game and the
+/// moves taken back are left out. The rules are those of the result if the
+/// game has ended, and those of `game_start` otherwise.
+pub fn to_record(entries: &[Entry]) -> Result<GameRecord, String> {
+    let mut record = None;
+    let mut turn_started = 0;
+    let mut undone = Vec::new();
+
+    for entry in entries {
+        let record = match (&entry.event, record.as_mut()) {
+            (Event::GameStart { board, rules }, _) => {
+                let mut started = GameRecord::new(board.clone(), TurnPlayer::Dark);
+                started.rules = *rules;
+                record = Some(started);
+                continue;
+            }
+            (_, Some(record)) => record,
+            (_, None) => return Err("the log does not start with game_start".to_string()),
+        };
+
+        match &entry.event {
+            Event::StartTurn { turn } => {
+                if record.moves.is_empty() {
+                    record.first_turn = *turn;
+                }
+                turn_started = entry.time;
+            }
+            Event::SkipTurn { turn } => {
+                undone.clear();
+                record.moves.push(RecordedMove::new(*turn, None));
+            }
+            Event::Mov { pos, turn } => {
+                let mut mov = RecordedMove::new(*turn, Some(pos.clone()));
+                mov.time = Some(entry.time.saturating_sub(turn_started) as f64 / 1000.0);
+                undone.clear();
+                record.moves.push(mov);
+            }
+            Event::Undo { plies, .. } => {
+                if *plies > record.moves.len() {
+                    return Err(format!("{} moves can not be taken back", plies));
+                }
+                let start = record.moves.len() - plies;
+                undone.extend(record.moves.drain(start..).rev());
+            }
+            Event::Redo { plies, .. } => {
+                if *plies > undone.len() {
+                    return Err(format!("{} moves can not be played again", plies));
+                }
+                let start = undone.len() - plies;
+                record.moves.extend(undone.drain(start..).rev());
+            }
+            Event::GameEnd { result, .. } => {
+                let ending = match result.outcome {
+                    Outcome::Normal => Ending::Normal,
+                    Outcome::Timeout(_) => Ending::Timeout,
+                    Outcome::Resignation(_) => Ending::Resignation,
+                    Outcome::Forfeit(_) => Ending::Forfeit,
+                    Outcome::DrawAgreed => Ending::Agreement,
+                    Outcome::Aborted => Ending::Aborted,
+                };
+                let squares = (record.start.size() * record.start.size()) as f64;
+                let score = match (result.outcome, result.outcome.loser()) {
+                    (Outcome::DrawAgreed, _) => 0.0,
+                    // The winner is given all the squares.
+                    (_, Some(TurnPlayer::Dark)) => -squares,
+                    (_, Some(TurnPlayer::Light)) => squares,
+                    (_, None) => result.dark_disks as f64 - result.light_disks as f64,
+                };
+                record.rules = result.rules;
+                record.result = Some(RecordResult { score, ending });
+            }
+            Event::GameStart { .. }
+            | Event::IllegalMov { .. }
+            | Event::Update { .. }
+            | Event::DrawOffer { .. } => (),
+        }
+    }
+
+    let mut record = record.ok_or("the log has no game")?;
+    while record.moves.last().is_some_and(|mov| mov.pos.is_none()) {
+        record.moves.pop();
+    }
+    record.validate()?;
+    Ok(record)
+}
+
+impl<W: Write> IO for JsonLog<W> {
+    fn game_start(&self, board: &Board) {
+        self.log(Event::GameStart {
+            board: board.clone(),
+            rules: self.rules,
+        });
+    }
+
+    fn skip_turn(&self, turn: &TurnPlayer) {
+        self.log(Event::SkipTurn { turn: *turn });
+    }
+
+    fn start_turn(&self, turn: &TurnPlayer, _time_left: Option<TimeLeft>) {
+        self.log(Event::StartTurn { turn: *turn });
+    }
+
+    fn before_mov(&self, _board: &Board, _turn: &TurnPlayer) {}
+
+    fn after_illegal_mov(&self, error: &PlaceError, turn: &TurnPlayer, retry: Retry) {
+        self.log(Event::IllegalMov {
+            pos: error.pos().clone(),
+            turn: *turn,
+            reason: error.into(),
+            retry,
+        });
+    }
+
+    fn after_mov(&self, pos: &Position, turn: &TurnPlayer) {
+        self.log(Event::Mov {
+            pos: pos.clone(),
+            turn: *turn,
+        });
+    }
+
+    fn after_update(&self, board: &Board) {
+        self.log(Event::Update {
+            board: board.clone(),
+        });
+    }
+
+    fn game_end(&self, board: &Board, result: &GameResult) {
+        self.log(Event::GameEnd {
+            board: board.clone(),
+            result: result.clone(),
+        });
+    }
+
+    fn after_undo(&self, plies: usize, board: &Board) {
+        self.log(Event::Undo {
+            plies,
+            board: board.clone(),
+        });
+    }
+
+    fn after_redo(&self, plies: usize, board: &Board) {
+        self.log(Event::Redo {
+            plies,
+            board: board.clone(),
+        });
+    }
+
+    fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+        self.log(Event::DrawOffer {
+            turn: *turn,
+            accepted,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::Disk;
+    use crate::core::player::{Action, MoveContext, Player};
+    use crate::core::ruversi::{IllegalMovPolicy, Ruversi};
+    use crate::player::computer::Computer;
+
+    /// Shares the written bytes with the test after the log is moved into
+    /// `Ruversi`.
+    #[derive(Clone, Default)]
+    struct Shared(std::rc::Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_game_log() {
+        let buf = Shared::default();
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(Computer::new(1)),
+            Box::new(Computer::new(1)),
+            Box::new(JsonLog::new(buf.clone())),
+        );
+        ruversi.run();
+
+        let bytes = buf.0.borrow().clone();
+        let entries = read_entries(bytes.as_slice()).unwrap();
+        assert_eq!(
+            entries[0].event,
+            Event::GameStart {
+                board: Board::initial(),
+                rules: RuleSet::Standard,
+            }
+        );
+        assert_eq!(
+            entries[1].event,
+            Event::StartTurn {
+                turn: TurnPlayer::Dark
+            }
+        );
+        assert!(entries.windows(2).all(|w| w[0].time <= w[1].time));
+
+        // Replaying the moves reaches the final board.
+        let mut board = Board::initial();
+        for entry in &entries {
+            if let Event::Mov { pos, turn } = &entry.event {
+                board.place(pos.clone(), turn.into_disk()).unwrap();
+            }
+        }
+        let record = to_record(&entries).unwrap();
+        assert_eq!(record.final_board().unwrap(), board);
+        assert!(record.moves.last().unwrap().pos.is_some());
+
+        match &entries.last().unwrap().event {
+            Event::GameEnd { board: end, result } => {
+                assert_eq!(end, &board);
+                assert_eq!(result.dark_disks, board.count_disks(&Disk::Dark));
+            }
+            event => panic!("unexpected last event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let log = JsonLog::new(Vec::new()).with_rules(RuleSet::AntiReversi);
+        let board = Board::initial();
+        log.game_start(&board);
+        let bytes = log.into_inner();
+        let entries = read_entries(bytes.as_slice()).unwrap();
+        assert_eq!(
+            entries[0].event,
+            Event::GameStart {
+                board: board.clone(),
+                rules: RuleSet::AntiReversi,
+            }
+        );
+        assert_eq!(to_record(&entries).unwrap().rules, RuleSet::AntiReversi);
+
+        // A log without the rules is of a standard game until its result
+        // tells otherwise.
+        let log = JsonLog::new(Vec::new());
+        log.game_start(&board);
+        log.game_end(&board, &GameResult::new(RuleSet::AntiReversi, 2, 2));
+        let bytes = log.into_inner();
+        let entries = read_entries(bytes.as_slice()).unwrap();
+        assert_eq!(
+            entries[0].event,
+            Event::GameStart {
+                board: board.clone(),
+                rules: RuleSet::Standard,
+            }
+        );
+        assert_eq!(to_record(&entries).unwrap().rules, RuleSet::AntiReversi);
+    }
+
+    /// Plays on a1 until it forfeits.
+    struct Illegal;
+
+    impl Player for Illegal {
+        fn mov(&mut self, _ctx: &MoveContext) -> Action {
+            Action::Move(Position::new(0, 0))
+        }
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let buf = Shared::default();
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(Illegal),
+            Box::new(Computer::new(1)),
+            Box::new(JsonLog::new(buf.clone())),
+        );
+        ruversi.set_illegal_mov_policy(IllegalMovPolicy::parse("2").unwrap());
+        ruversi.run();
+
+        let bytes = buf.0.borrow().clone();
+        let entries = read_entries(bytes.as_slice()).unwrap();
+        let illegal: Vec<_> = entries
+            .iter()
+            .filter_map(|entry| match &entry.event {
+                Event::IllegalMov { reason, retry, .. } => Some((*reason, *retry)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            illegal,
+            vec![
+                (IllegalReason::NothingToTurn, Retry::Again(Some(1))),
+                (IllegalReason::NothingToTurn, Retry::Forfeit),
+            ]
+        );
+        assert!(String::from_utf8(bytes)
+            .unwrap()
+            .contains(r#""reason":"nothing_to_turn","retry":{"again":1}}"#));
+
+        let record = to_record(&entries).unwrap();
+        assert!(record.moves.is_empty());
+        let result = record.result.unwrap();
+        assert_eq!(result.ending, Ending::Forfeit);
+        assert_eq!(result.score, -64.0);
+    }
+
+    #[test]
+    fn test_line() {
+        let log = JsonLog::new(Vec::new());
+        log.after_mov(&Position::new(3, 2), &TurnPlayer::Dark);
+        let line = String::from_utf8(log.into_inner()).unwrap();
+
+        assert!(line.ends_with(",\"type\":\"mov\",\"pos\":\"d3\",\"turn\":\"dark\"}\n"));
+        assert!(read_entries("{\"time\":0,\"type\":\"nap\"}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_undo_redo_record() {
+        let log = JsonLog::new(Vec::new());
+        let mut board = Board::initial();
+        log.game_start(&board);
+
+        // Two moves of dark are played, and the second is replaced.
+        let mut turn = TurnPlayer::Dark;
+        let mut movs = Vec::new();
+        for _ in 0..3 {
+            let pos = board.legal_movs(turn.into_disk())[0].clone();
+            board.place(pos.clone(), turn.into_disk()).unwrap();
+            log.after_mov(&pos, &turn);
+            movs.push(Some(pos));
+            turn = TurnPlayer::other(&turn);
+        }
+        log.after_undo(2, &board);
+        log.after_redo(1, &board);
+        let mut board = Board::initial();
+        board.place(movs[0].clone().unwrap(), Disk::Dark).unwrap();
+        board.place(movs[1].clone().unwrap(), Disk::Light).unwrap();
+        movs[2] = board.legal_movs(Disk::Dark).last().cloned();
+        log.after_mov(movs[2].as_ref().unwrap(), &TurnPlayer::Dark);
+
+        let bytes = log.into_inner();
+        let entries = read_entries(bytes.as_slice()).unwrap();
+        let record = to_record(&entries).unwrap();
+        let recorded: Vec<_> = record.moves.iter().map(|mov| mov.pos.clone()).collect();
+        assert_eq!(recorded, movs);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::core::board::{Board, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::ruversi::{GameResult, Retry, TurnPlayer, IO};
+
+/// Forwards every event to each child `IO` in order, e.g. to show the board
+/// on the screen and log the game to a file at the same time.
+#[derive(Default)]
+pub struct MultiIO {
+    ios: Vec<Box<dyn IO>>,
+}
+
+impl MultiIO {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, io: Box<dyn IO>) -> Self {
+        self.push(io);
+        self
+    }
+
+    pub fn push(&mut self, io: Box<dyn IO>) {
+        self.ios.push(io);
+    }
+
+    pub fn len(&self) -> usize {
+        self.ios.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ios.is_empty()
+    }
+}
+
+impl From<Vec<Box<dyn IO>>> for MultiIO {
+    fn from(ios: Vec<Box<dyn IO>>) -> Self {
+        Self { ios }
+    }
+}
+
+impl IO for MultiIO {
+    fn game_start(&self, board: &Board) {
+        self.ios.iter().for_each(|io| io.game_start(board));
+    }
+
+    fn skip_turn(&self, turn: &TurnPlayer) {
+        self.ios.iter().for_each(|io| io.skip_turn(turn));
+    }
+
+    fn start_turn(&self, turn: &TurnPlayer, time_left: Option<TimeLeft>) {
+        self.ios
+            .iter()
+            .for_each(|io| io.start_turn(turn, time_left));
+    }
+
+    fn before_mov(&self, board: &Board, turn: &TurnPlayer) {
+        self.ios.iter().for_each(|io| io.before_mov(board, turn));
+    }
+
+    fn after_illegal_mov(&self, error: &PlaceError, turn: &TurnPlayer, retry: Retry) {
+        self.ios
+            .iter()
+            .for_each(|io| io.after_illegal_mov(error, turn, retry));
+    }
+
+    fn after_mov(&self, pos: &Position, turn: &TurnPlayer) {
+        self.ios.iter().for_each(|io| io.after_mov(pos, turn));
+    }
+
+    fn after_update(&self, board: &Board) {
+        self.ios.iter().for_each(|io| io.after_update(board));
+    }
+
+    fn game_end(&self, board: &Board, result: &GameResult) {
+        self.ios.iter().for_each(|io| io.game_end(board, result));
+    }
+
+    fn after_undo(&self, plies: usize, board: &Board) {
+        self.ios.iter().for_each(|io| io.after_undo(plies, board));
+    }
+
+    fn after_redo(&self, plies: usize, board: &Board) {
+        self.ios.iter().for_each(|io| io.after_redo(plies, board));
+    }
+
+    fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+        self.ios
+            .iter()
+            .for_each(|io| io.after_draw_offer(turn, accepted));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::core::ruversi::Ruversi;
+    use crate::player::computer::Computer;
+
+    /// Records the names of the events with a tag.
+    #[derive(Clone)]
+    struct Recorder {
+        tag: &'static str,
+        events: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, event: &str) {
+            self.events
+                .borrow_mut()
+                .push(format!("{}:{}", self.tag, event));
+        }
+    }
+
+    impl IO for Recorder {
+        fn game_start(&self, _board: &Board) {
+            self.record("game_start");
+        }
+        fn skip_turn(&self, _turn: &TurnPlayer) {
+            self.record("skip_turn");
+        }
+        fn start_turn(&self, _turn: &TurnPlayer, _time_left: Option<TimeLeft>) {
+            self.record("start_turn");
+        }
+        fn before_mov(&self, _board: &Board, _turn: &TurnPlayer) {
+            self.record("before_mov");
+        }
+        fn after_illegal_mov(&self, _error: &PlaceError, _turn: &TurnPlayer, _retry: Retry) {
+            self.record("after_illegal_mov");
+        }
+        fn after_mov(&self, _pos: &Position, _turn: &TurnPlayer) {
+            self.record("after_mov");
+        }
+        fn after_update(&self, _board: &Board) {
+            self.record("after_update");
+        }
+        fn game_end(&self, _board: &Board, _result: &GameResult) {
+            self.record("game_end");
+        }
+    }
+
+    fn recorder(tag: &'static str, events: &Rc<RefCell<Vec<String>>>) -> Box<dyn IO> {
+        Box::new(Recorder {
+            tag,
+            events: Rc::clone(events),
+        })
+    }
+
+    #[test]
+    fn test_forward_in_order() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let multi = MultiIO::new()
+            .with(recorder("a", &events))
+            .with(recorder("b", &events));
+        assert_eq!(multi.len(), 2);
+
+        multi.game_start(&Board::initial());
+        multi.after_mov(&Position::new(3, 2), &TurnPlayer::Dark);
+        assert_eq!(
+            *events.borrow(),
+            vec!["a:game_start", "b:game_start", "a:after_mov", "b:after_mov"]
+        );
+    }
+
+    #[test]
+    fn test_observer() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(Computer::new(1)),
+            Box::new(Computer::new(1)),
+            recorder("io", &events),
+        );
+        ruversi.add_observer(recorder("io", &observed));
+        ruversi.run();
+
+        assert_eq!(events.borrow().last().unwrap(), "io:game_end");
+        assert_eq!(*events.borrow(), *observed.borrow());
+    }
+}
@@ -0,0 +1,567 @@
+use std::cell::RefCell;
+use std::io::{stdout, Write};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::core::board::{Board, Disk, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::player::Action;
+use crate::core::ruversi::{GameResult, Input, Outcome, Retry, TurnPlayer, IO};
+
+const BOARD_LEFT: u16 = 2;
+const BOARD_TOP: u16 = 2;
+/// The columns between the right edge of the board and the side panel.
+const PANEL_GAP: u16 = 4;
+const HISTORY_ROWS: usize = 12;
+
+/// A full-screen terminal UI. Players move a cursor over the board with the
+/// arrow keys and place a disk with Enter; a side panel shows whose turn it
+/// is, disk counts, time used by each player and the move history.
+///
+/// Clones share the same screen state, so the same `TUI` can be handed to
+/// `Ruversi` as the `IO` and to each `User` as the `Input`.
+#[derive(Clone)]
+pub struct TUI {
+    state: Rc<RefCell<State>>,
+}
+
+struct State {
+    active: bool,
+    /// Whether reading the terminal has failed, so no more input will come.
+    lost: bool,
+    /// Whether the user has quit with ctrl-c.
+    quit: bool,
+    board: Board,
+    turn: Option<TurnPlayer>,
+    cursor: Position,
+    last_mov: Option<Position>,
+    history: Vec<String>,
+    message: String,
+    dark_time: Duration,
+    light_time: Duration,
+    turn_started: Option<Instant>,
+    time_left: Option<TimeLeft>,
+    /// The seconds used by dark and light when the panel was last drawn.
+    shown_time: Option<(u64, u64)>,
+}
+
+impl Default for TUI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TUI {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State::new())),
+        }
+    }
+
+    /// Whether the user has quit with ctrl-c, which aborts the game.
+    pub fn is_quit(&self) -> bool {
+        self.state.borrow().quit
+    }
+
+    /// Whether the terminal could not be read, which aborts the game.
+    pub fn is_lost(&self) -> bool {
+        self.state.borrow().lost
+    }
+
+    fn get_mark(turn: &TurnPlayer) -> &'static str {
+        match turn {
+            TurnPlayer::Dark => "●",
+            TurnPlayer::Light => "○",
+        }
+    }
+
+    fn with_state<F: FnOnce(&mut State)>(&self, f: F) {
+        let mut state = self.state.borrow_mut();
+        f(&mut state);
+        let _ = state.draw();
+    }
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            active: false,
+            lost: false,
+            quit: false,
+            board: Board::new(),
+            turn: None,
+            cursor: Position::new(3, 3),
+            last_mov: None,
+            history: Vec::new(),
+            message: String::new(),
+            dark_time: Duration::ZERO,
+            light_time: Duration::ZERO,
+            turn_started: None,
+            time_left: None,
+            shown_time: None,
+        }
+    }
+
+    fn enter(&mut self) -> std::io::Result<()> {
+        if !self.active {
+            terminal::enable_raw_mode()?;
+            queue!(stdout(), EnterAlternateScreen, Hide)?;
+            self.active = true;
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self) -> std::io::Result<()> {
+        if self.active {
+            queue!(stdout(), Show, LeaveAlternateScreen)?;
+            stdout().flush()?;
+            terminal::disable_raw_mode()?;
+            self.active = false;
+        }
+        Ok(())
+    }
+
+    fn stop_clock(&mut self) {
+        if let (Some(turn), Some(started)) = (self.turn, self.turn_started.take()) {
+            match turn {
+                TurnPlayer::Dark => self.dark_time += started.elapsed(),
+                TurnPlayer::Light => self.light_time += started.elapsed(),
+            }
+        }
+    }
+
+    fn used_time(&self, turn: TurnPlayer) -> Duration {
+        let running = match (self.turn, self.turn_started) {
+            (Some(t), Some(started)) if t == turn => started.elapsed(),
+            _ => Duration::ZERO,
+        };
+        match turn {
+            TurnPlayer::Dark => self.dark_time + running,
+            TurnPlayer::Light => self.light_time + running,
+        }
+    }
+
+    /// The whole seconds used by dark and light, as shown on the panel.
+    fn used_secs(&self) -> (u64, u64) {
+        (
+            self.used_time(TurnPlayer::Dark).as_secs(),
+            self.used_time(TurnPlayer::Light).as_secs(),
+        )
+    }
+
+    /// The upper left of the four center squares.
+    fn center(board: &Board) -> Position {
+        let c = board.size() as i32 / 2 - 1;
+        Position::new(c, c)
+    }
+
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let last = self.board.size() as i32 - 1;
+        let x = (self.cursor.x + dx).clamp(0, last);
+        let y = (self.cursor.y + dy).clamp(0, last);
+        self.cursor = Position::new(x, y);
+    }
+
+    fn panel_left(&self) -> u16 {
+        BOARD_LEFT + 2 + 2 * self.board.size() as u16 + PANEL_GAP
+    }
+
+    /// The first row below the board.
+    fn below_board(&self) -> u16 {
+        BOARD_TOP + 1 + self.board.size() as u16
+    }
+
+    /// Redraws the panel once the time used has changed, as the rest of the
+    /// screen is drawn whenever the state changes.
+    fn tick(&mut self) -> std::io::Result<()> {
+        if !self.active || self.shown_time == Some(self.used_secs()) {
+            return Ok(());
+        }
+        let mut out = stdout();
+        self.draw_panel(&mut out)?;
+        out.flush()
+    }
+
+    fn draw(&mut self) -> std::io::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+
+        let mut out = stdout();
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(BOARD_LEFT, 0),
+            Print("Ruversi")
+        )?;
+
+        let size = self.board.size() as i32;
+        let columns: Vec<String> = (0..size)
+            .map(|x| ((b'a' + x as u8) as char).to_string())
+            .collect();
+        queue!(
+            out,
+            MoveTo(BOARD_LEFT + 3, BOARD_TOP),
+            Print(columns.join(" "))
+        )?;
+        for y in 0..size {
+            queue!(
+                out,
+                MoveTo(BOARD_LEFT, BOARD_TOP + 1 + y as u16),
+                Print(y + 1)
+            )?;
+            for x in 0..size {
+                let pos = Position::new(x, y);
+                self.draw_cell(&mut out, &pos)?;
+            }
+            queue!(
+                out,
+                SetBackgroundColor(Color::DarkGreen),
+                Print(" "),
+                ResetColor
+            )?;
+        }
+
+        self.draw_panel(&mut out)?;
+
+        queue!(
+            out,
+            MoveTo(BOARD_LEFT, self.below_board() + 1),
+            Print(&self.message),
+            MoveTo(BOARD_LEFT, self.below_board() + 3),
+            Print("arrows: move   enter: place   u: undo   r: redo"),
+            MoveTo(BOARD_LEFT, self.below_board() + 4),
+            Print("R: resign   d: offer a draw   a: abort   ctrl-c: quit"),
+        )?;
+
+        out.flush()
+    }
+
+    fn draw_cell(&self, out: &mut impl Write, pos: &Position) -> std::io::Result<()> {
+        let bg = if *pos == self.cursor && self.turn.is_some() {
+            Color::DarkYellow
+        } else if self.last_mov.as_ref() == Some(pos) {
+            Color::DarkCyan
+        } else {
+            Color::DarkGreen
+        };
+        let (fg, mark) = match self.board.get(pos) {
+            Some(Disk::Dark) => (Color::Black, "●"),
+            Some(Disk::Light) => (Color::White, "●"),
+            None => match self.turn {
+                Some(turn) if self.board.can_place(pos.clone(), turn.into_disk()) => {
+                    (Color::Green, "·")
+                }
+                _ => (Color::DarkGreen, " "),
+            },
+        };
+
+        queue!(
+            out,
+            MoveTo(
+                BOARD_LEFT + 2 + 2 * pos.x as u16,
+                BOARD_TOP + 1 + pos.y as u16
+            ),
+            SetBackgroundColor(Color::DarkGreen),
+            Print(" "),
+            SetBackgroundColor(bg),
+            SetForegroundColor(fg),
+            Print(mark),
+            ResetColor,
+        )
+    }
+
+    fn draw_panel(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        self.shown_time = Some(self.used_secs());
+        let turn = match (self.turn, self.time_left) {
+            (Some(turn), Some(time_left)) => {
+                format!("{} {}  ({} left)", TUI::get_mark(&turn), turn, time_left)
+            }
+            (Some(turn), None) => format!("{} {}", TUI::get_mark(&turn), turn),
+            (None, _) => "-".to_string(),
+        };
+        let dark = self.board.count_disks(&Disk::Dark);
+        let light = self.board.count_disks(&Disk::Light);
+        let left = self.panel_left();
+
+        queue!(
+            out,
+            MoveTo(left, BOARD_TOP),
+            Print(format!("Turn: {}", turn)),
+            MoveTo(left, BOARD_TOP + 2),
+            Print(format!(
+                "● Dark  {:>2}  {}",
+                dark,
+                format_duration(self.used_time(TurnPlayer::Dark))
+            )),
+            MoveTo(left, BOARD_TOP + 3),
+            Print(format!(
+                "○ Light {:>2}  {}",
+                light,
+                format_duration(self.used_time(TurnPlayer::Light))
+            )),
+            MoveTo(left, BOARD_TOP + 5),
+            Print("Moves:"),
+        )?;
+
+        let skip = self.history.len().saturating_sub(HISTORY_ROWS);
+        for (i, mov) in self.history.iter().enumerate().skip(skip) {
+            queue!(
+                out,
+                MoveTo(left, BOARD_TOP + 6 + (i - skip) as u16),
+                Print(format!("{:>3}. {}", i + 1, mov)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+impl IO for TUI {
+    fn game_start(&self, board: &Board) {
+        self.with_state(|state| {
+            let _ = state.enter();
+            state.board = board.clone();
+            state.cursor = State::center(board);
+            state.message = "Game start".to_string();
+        });
+    }
+
+    fn skip_turn(&self, turn: &TurnPlayer) {
+        self.with_state(|state| {
+            state.stop_clock();
+            state.history.push(format!("{} pass", TUI::get_mark(turn)));
+            state.message = format!("There is no place to a {} disk.", TUI::get_mark(turn));
+        });
+    }
+
+    fn start_turn(&self, turn: &TurnPlayer, time_left: Option<TimeLeft>) {
+        self.with_state(|state| {
+            state.turn = Some(*turn);
+            state.turn_started = Some(Instant::now());
+            state.time_left = time_left;
+        });
+    }
+
+    fn before_mov(&self, board: &Board, _turn: &TurnPlayer) {
+        self.with_state(|state| state.board = board.clone());
+    }
+
+    fn after_illegal_mov(&self, error: &PlaceError, _turn: &TurnPlayer, retry: Retry) {
+        self.with_state(|state| {
+            state.message = match retry {
+                Retry::Again(None) | Retry::Forfeit => format!("Illegal move: {}.", error),
+                Retry::Again(Some(left)) => {
+                    format!("Illegal move: {}. {} attempts left.", error, left)
+                }
+                Retry::RandomMove => {
+                    format!("Illegal move: {}. A random move is played.", error)
+                }
+            };
+        });
+    }
+
+    fn after_mov(&self, pos: &Position, turn: &TurnPlayer) {
+        self.with_state(|state| {
+            state.stop_clock();
+            state.last_mov = Some(pos.clone());
+            state
+                .history
+                .push(format!("{} {}", TUI::get_mark(turn), pos.to_notation()));
+            state.message.clear();
+        });
+    }
+
+    fn after_update(&self, board: &Board) {
+        self.with_state(|state| state.board = board.clone());
+    }
+
+    fn game_end(&self, board: &Board, result: &GameResult) {
+        self.with_state(|state| {
+            state.board = board.clone();
+            state.turn = None;
+            let score = format!("{} : {}", result.dark_disks, result.light_disks);
+            let text = match (result.winner, result.outcome) {
+                (_, Outcome::Aborted) => format!("Aborted {}", score),
+                (None, Outcome::DrawAgreed) => format!("Draw agreed {}", score),
+                (None, _) => format!("Draw {}", score),
+                (Some(player), outcome) => format!(
+                    "{} WIN {}{}",
+                    TUI::get_mark(&player),
+                    score,
+                    match outcome {
+                        Outcome::Timeout(_) => " on time",
+                        Outcome::Resignation(_) => " by resignation",
+                        Outcome::Forfeit(_) => " by forfeit",
+                        _ => "",
+                    }
+                ),
+            };
+            state.message = format!("{}  (press any key)", text);
+        });
+
+        let mut state = self.state.borrow_mut();
+        if state.active && !state.quit {
+            while !matches!(
+                event::read(),
+                Ok(Event::Key(KeyEvent {
+                    kind: KeyEventKind::Press,
+                    ..
+                })) | Err(_)
+            ) {}
+        }
+        let _ = state.leave();
+    }
+
+    fn after_undo(&self, plies: usize, board: &Board) {
+        self.with_state(|state| {
+            state.board = board.clone();
+            state.last_mov = None;
+            state.message = format!("{} moves taken back.", plies);
+        });
+    }
+
+    fn after_redo(&self, plies: usize, board: &Board) {
+        self.with_state(|state| {
+            state.board = board.clone();
+            state.last_mov = None;
+            state.message = format!("{} moves played again.", plies);
+        });
+    }
+
+    fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+        if !accepted {
+            self.with_state(|state| {
+                state.message = format!(
+                    "{} offered a draw, which was declined.",
+                    TUI::get_mark(turn)
+                );
+            });
+        }
+    }
+}
+
+impl Input for TUI {
+    /// Returns the first legal move once the terminal is lost or the user
+    /// has quit, as only a position can be returned.
+    fn input_pos(&self) -> Position {
+        loop {
+            match self.input_action() {
+                Action::Move(pos) => return pos,
+                Action::Abort if self.is_lost() || self.is_quit() => {
+                    let state = self.state.borrow();
+                    return state
+                        .turn
+                        .and_then(|turn| {
+                            state.board.legal_movs(turn.into_disk()).into_iter().next()
+                        })
+                        .unwrap_or_else(|| state.cursor.clone());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Aborts the game on ctrl-c or once the terminal can not be read any
+    /// more.
+    fn input_action(&self) -> Action {
+        loop {
+            let _ = self.state.borrow_mut().tick();
+            let key = match event::poll(Duration::from_millis(250)) {
+                Ok(false) => continue,
+                Ok(true) => event::read(),
+                Err(e) => Err(e),
+            };
+            let key = match key {
+                Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+                Ok(_) => continue,
+                Err(_) => {
+                    self.state.borrow_mut().lost = true;
+                    return Action::Abort;
+                }
+            };
+
+            let mut state = self.state.borrow_mut();
+            match key.code {
+                KeyCode::Up => state.move_cursor(0, -1),
+                KeyCode::Down => state.move_cursor(0, 1),
+                KeyCode::Left => state.move_cursor(-1, 0),
+                KeyCode::Right => state.move_cursor(1, 0),
+                KeyCode::Enter | KeyCode::Char(' ') => return Action::Move(state.cursor.clone()),
+                KeyCode::Char('u') => return Action::Undo,
+                KeyCode::Char('r') => return Action::Redo,
+                KeyCode::Char('R') => return Action::Resign,
+                KeyCode::Char('d') => return Action::OfferDraw,
+                KeyCode::Char('a') => return Action::Abort,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    state.quit = true;
+                    return Action::Abort;
+                }
+                _ => continue,
+            }
+            let _ = state.draw();
+        }
+    }
+
+    fn input_accept_draw(&self) -> bool {
+        self.with_state(|state| state.message = "A draw is offered. Accept? (y/n)".to_string());
+        loop {
+            let key = match event::read() {
+                Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+                Ok(_) => continue,
+                // The offer is declined once the terminal can not be read.
+                Err(_) => {
+                    self.state.borrow_mut().lost = true;
+                    return false;
+                }
+            };
+            match key.code {
+                KeyCode::Char('y') => return true,
+                KeyCode::Char('n') => return false,
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_cursor_stays_on_board() {
+        let mut state = State::new();
+        state.move_cursor(-5, 1);
+        assert_eq!(state.cursor, Position::new(0, 4));
+        state.move_cursor(10, 10);
+        assert_eq!(state.cursor, Position::new(7, 7));
+
+        state.board = Board::with_size(6).unwrap();
+        state.move_cursor(10, -1);
+        assert_eq!(state.cursor, Position::new(5, 5));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(125)), "02:05");
+    }
+}
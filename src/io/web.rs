@@ -0,0 +1,446 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::core::board::{Board, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::player::Action;
+use crate::core::ruversi::{GameResult, Input, Retry, TurnPlayer, IO};
+
+const INDEX_HTML: &str = include_str!("web/index.html");
+const LONG_POLL_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// A board in the browser, served over HTTP from `localhost`.
+///
+/// `GET /` serves the page, which long-polls `GET /events?since=N` for the
+/// game events as JSON and places disks with `POST /move?pos=d3`. The other
+/// actions are `POST /action?name=undo`, `redo`, `resign`, `draw` to offer
+/// a draw and `abort`, and an offer is answered with
+/// `POST /draw?accept=yes` or `no`. Clones share the same server, so the
+/// same `WebUI` can be handed to `Ruversi` as the `IO` and to each `User` as
+/// the `Input`.
+#[derive(Clone)]
+pub struct WebUI {
+    shared: Arc<Shared>,
+    addr: SocketAddr,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    events: Vec<String>,
+    turn: Option<TurnPlayer>,
+    awaiting_input: bool,
+    action: Option<Action>,
+    awaiting_answer: bool,
+    answer: Option<bool>,
+}
+
+fn color_name(turn: &TurnPlayer) -> &'static str {
+    match turn {
+        TurnPlayer::Dark => "dark",
+        TurnPlayer::Light => "light",
+    }
+}
+
+impl Shared {
+    fn push_event(&self, event: String) {
+        self.state.lock().unwrap().events.push(event);
+        self.changed.notify_all();
+    }
+
+    /// Waits until there are events from `since` on, and returns them as a
+    /// JSON object with the sequence number to ask for next.
+    fn wait_events(&self, since: usize, timeout: Duration) -> String {
+        let state = self.state.lock().unwrap();
+        let (state, _) = self
+            .changed
+            .wait_timeout_while(state, timeout, |state| state.events.len() <= since)
+            .unwrap();
+
+        let since = since.min(state.events.len());
+        format!(
+            r#"{{"next":{},"events":[{}]}}"#,
+            state.events.len(),
+            state.events[since..].join(",")
+        )
+    }
+
+    /// Hands an action to a waiting `input_action`. Returns `false` if no
+    /// action is being waited for.
+    fn act(&self, action: Action) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.awaiting_input {
+            return false;
+        }
+        state.action = Some(action);
+        self.changed.notify_all();
+        true
+    }
+
+    /// Hands the answer to a draw offer to a waiting `input_accept_draw`.
+    /// Returns `false` if no answer is being waited for.
+    fn answer(&self, accept: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.awaiting_answer {
+            return false;
+        }
+        state.answer = Some(accept);
+        self.changed.notify_all();
+        true
+    }
+}
+
+impl WebUI {
+    /// Starts serving on `addr`, e.g. `127.0.0.1:8080`.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+        });
+
+        let server = Arc::clone(&shared);
+        thread::spawn(move || {
+            for stream in listener.incoming().map_while(Result::ok) {
+                let shared = Arc::clone(&server);
+                thread::spawn(move || {
+                    let _ = Self::handle(stream, &shared);
+                });
+            }
+        });
+
+        Ok(Self { shared, addr })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn handle(stream: TcpStream, shared: &Shared) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut words = request_line.split_whitespace();
+        let method = words.next().unwrap_or("");
+        let target = words.next().unwrap_or("");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let param = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value)
+        };
+
+        match (method, path) {
+            ("GET", "/") => Self::respond(stream, "200 OK", "text/html; charset=utf-8", INDEX_HTML),
+            ("GET", "/events") => {
+                let since = param("since").and_then(|s| s.parse().ok()).unwrap_or(0);
+                let body = shared.wait_events(since, LONG_POLL_TIMEOUT);
+                Self::respond(stream, "200 OK", "application/json", &body)
+            }
+            ("POST", "/move") => match param("pos").map(Position::try_from_notation) {
+                Some(Ok(pos)) => Self::respond_done(stream, shared.act(Action::Move(pos))),
+                _ => Self::respond(stream, "400 Bad Request", "text/plain", "invalid pos"),
+            },
+            ("POST", "/action") => {
+                let action = match param("name") {
+                    Some("undo") => Action::Undo,
+                    Some("redo") => Action::Redo,
+                    Some("resign") => Action::Resign,
+                    Some("draw") => Action::OfferDraw,
+                    Some("abort") => Action::Abort,
+                    _ => {
+                        return Self::respond(
+                            stream,
+                            "400 Bad Request",
+                            "text/plain",
+                            "invalid name",
+                        )
+                    }
+                };
+                Self::respond_done(stream, shared.act(action))
+            }
+            ("POST", "/draw") => match param("accept") {
+                Some("yes") => Self::respond_done(stream, shared.answer(true)),
+                Some("no") => Self::respond_done(stream, shared.answer(false)),
+                _ => Self::respond(stream, "400 Bad Request", "text/plain", "invalid accept"),
+            },
+            _ => Self::respond(stream, "404 Not Found", "text/plain", "not found"),
+        }
+    }
+
+    fn respond(
+        mut stream: TcpStream,
+        status: &str,
+        content_type: &str,
+        body: &str,
+    ) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+
+    /// Responds to a request for an action, which is refused if nothing is
+    /// waited for.
+    fn respond_done(stream: TcpStream, done: bool) -> io::Result<()> {
+        match done {
+            true => Self::respond(stream, "204 No Content", "text/plain", ""),
+            false => Self::respond(stream, "409 Conflict", "text/plain", "not your turn"),
+        }
+    }
+
+    fn push_board_event(&self, kind: &str, board: &Board) {
+        self.shared.push_event(format!(
+            r#"{{"type":"{}","board":"{}"}}"#,
+            kind,
+            board.to_compact_str()
+        ));
+    }
+
+    fn push_turn_event(&self, kind: &str, turn: &TurnPlayer) {
+        self.shared.push_event(format!(
+            r#"{{"type":"{}","turn":"{}"}}"#,
+            kind,
+            color_name(turn)
+        ));
+    }
+
+    fn push_pos_event(&self, kind: &str, pos: &Position, turn: &TurnPlayer) {
+        self.shared.push_event(format!(
+            r#"{{"type":"{}","pos":"{}","turn":"{}"}}"#,
+            kind,
+            pos.to_notation(),
+            color_name(turn)
+        ));
+    }
+}
+
+impl IO for WebUI {
+    fn game_start(&self, board: &Board) {
+        self.push_board_event("game_start", board);
+    }
+
+    fn skip_turn(&self, turn: &TurnPlayer) {
+        self.push_turn_event("skip_turn", turn);
+    }
+
+    fn start_turn(&self, turn: &TurnPlayer, _time_left: Option<TimeLeft>) {
+        self.push_turn_event("start_turn", turn);
+    }
+
+    fn before_mov(&self, _board: &Board, turn: &TurnPlayer) {
+        self.shared.state.lock().unwrap().turn = Some(*turn);
+    }
+
+    fn after_illegal_mov(&self, error: &PlaceError, turn: &TurnPlayer, _retry: Retry) {
+        self.push_pos_event("illegal", error.pos(), turn);
+    }
+
+    fn after_mov(&self, pos: &Position, turn: &TurnPlayer) {
+        self.push_pos_event("mov", pos, turn);
+    }
+
+    fn after_update(&self, board: &Board) {
+        self.push_board_event("board", board);
+    }
+
+    fn game_end(&self, board: &Board, result: &GameResult) {
+        let winner = match &result.winner {
+            Some(turn) => format!(r#""{}""#, color_name(turn)),
+            None => "null".to_string(),
+        };
+        self.shared.push_event(format!(
+            r#"{{"type":"game_end","board":"{}","dark":{},"light":{},"winner":{},"outcome":{}}}"#,
+            board.to_compact_str(),
+            result.dark_disks,
+            result.light_disks,
+            winner,
+            serde_json::to_string(&result.outcome).expect("An outcome must be serializable.")
+        ));
+    }
+
+    fn after_undo(&self, plies: usize, board: &Board) {
+        self.shared.push_event(format!(
+            r#"{{"type":"undo","plies":{},"board":"{}"}}"#,
+            plies,
+            board.to_compact_str()
+        ));
+    }
+
+    fn after_redo(&self, plies: usize, board: &Board) {
+        self.shared.push_event(format!(
+            r#"{{"type":"redo","plies":{},"board":"{}"}}"#,
+            plies,
+            board.to_compact_str()
+        ));
+    }
+
+    fn after_draw_offer(&self, turn: &TurnPlayer, accepted: bool) {
+        self.shared.push_event(format!(
+            r#"{{"type":"draw_offer","turn":"{}","accepted":{}}}"#,
+            color_name(turn),
+            accepted
+        ));
+    }
+}
+
+impl Input for WebUI {
+    fn input_pos(&self) -> Position {
+        loop {
+            if let Action::Move(pos) = self.input_action() {
+                return pos;
+            }
+        }
+    }
+
+    fn input_action(&self) -> Action {
+        let turn = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.awaiting_input = true;
+            state.action = None;
+            state.turn.unwrap_or(TurnPlayer::Dark)
+        };
+        self.push_turn_event("input", &turn);
+
+        let state = self.shared.state.lock().unwrap();
+        let mut state = self
+            .shared
+            .changed
+            .wait_while(state, |state| state.action.is_none())
+            .unwrap();
+        state.awaiting_input = false;
+        state.action.take().expect("An action must have been made.")
+    }
+
+    fn input_accept_draw(&self) -> bool {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            state.awaiting_answer = true;
+            state.answer = None;
+        }
+        self.shared
+            .push_event(r#"{"type":"accept_draw"}"#.to_string());
+
+        let state = self.shared.state.lock().unwrap();
+        let mut state = self
+            .shared
+            .changed
+            .wait_while(state, |state| state.answer.is_none())
+            .unwrap();
+        state.awaiting_answer = false;
+        state.answer.take().expect("An answer must have been made.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn request(addr: SocketAddr, method: &str, target: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            method, target
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_events() {
+        let ui = WebUI::bind("127.0.0.1:0").unwrap();
+        ui.game_start(&Board::initial());
+        ui.start_turn(&TurnPlayer::Dark, None);
+
+        let response = request(ui.local_addr(), "GET", "/events?since=1");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(r#"{"next":2,"events":[{"type":"start_turn","turn":"dark"}]}"#));
+
+        let response = request(ui.local_addr(), "GET", "/");
+        assert!(response.contains("<title>Ruversi</title>"));
+    }
+
+    #[test]
+    fn test_click() {
+        let ui = WebUI::bind("127.0.0.1:0").unwrap();
+        let addr = ui.local_addr();
+        assert!(request(addr, "POST", "/move?pos=d3").starts_with("HTTP/1.1 409"));
+        assert!(request(addr, "POST", "/move?pos=z9").starts_with("HTTP/1.1 400"));
+
+        let clicker = thread::spawn(move || loop {
+            if request(addr, "POST", "/move?pos=d3").starts_with("HTTP/1.1 204") {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        });
+
+        ui.before_mov(&Board::initial(), &TurnPlayer::Dark);
+        assert_eq!(ui.input_pos(), Position::new(3, 2));
+        clicker.join().unwrap();
+    }
+
+    /// Posts to `target` until the request is taken.
+    fn post_until_taken(addr: SocketAddr, target: &'static str) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            while !request(addr, "POST", target).starts_with("HTTP/1.1 204") {
+                thread::sleep(Duration::from_millis(10));
+            }
+        })
+    }
+
+    #[test]
+    fn test_actions() {
+        let ui = WebUI::bind("127.0.0.1:0").unwrap();
+        let addr = ui.local_addr();
+        assert!(request(addr, "POST", "/action?name=resign").starts_with("HTTP/1.1 409"));
+        assert!(request(addr, "POST", "/action?name=fly").starts_with("HTTP/1.1 400"));
+        assert!(request(addr, "POST", "/draw?accept=yes").starts_with("HTTP/1.1 409"));
+
+        for (target, action) in [
+            ("/action?name=undo", Action::Undo),
+            ("/action?name=resign", Action::Resign),
+            ("/action?name=draw", Action::OfferDraw),
+            ("/action?name=abort", Action::Abort),
+        ] {
+            let poster = post_until_taken(addr, target);
+            assert_eq!(ui.input_action(), action);
+            poster.join().unwrap();
+        }
+
+        let poster = post_until_taken(addr, "/draw?accept=yes");
+        assert!(ui.input_accept_draw());
+        poster.join().unwrap();
+        ui.after_draw_offer(&TurnPlayer::Dark, true);
+
+        let events = request(addr, "GET", "/events?since=0");
+        assert!(events.contains(r#"{"type":"accept_draw"}"#));
+        assert!(events.contains(r#"{"type":"draw_offer","turn":"dark","accepted":true}"#));
+    }
+}
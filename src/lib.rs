@@ -0,0 +1,10 @@
+pub mod ai;
+pub mod analysis;
+pub mod core;
+pub mod io;
+pub mod net;
+pub mod player;
+pub mod protocol;
+pub mod record;
+pub mod replay;
+pub mod setup;
@@ -1,37 +1,399 @@
-use crate::core::{board::Board, ruversi::Ruversi};
-
-use io::cui::CUI;
-use player::user::User;
-
-mod core;
-mod io;
-mod player;
-
-#[rustfmt::skip]
-fn init_board() -> Board {
-    board_fig!(
-        "________",
-        "________",
-        "________",
-        "___ox___",
-        "___xo___",
-        "________",
-        "________",
-        "________"
-    )
+use std::net::TcpListener;
+use std::time::Duration;
+
+use ruversi::analysis::{self, AnalysisOptions};
+use ruversi::core::{
+    board::{notation, Board, BoardState},
+    clock::TimeControl,
+    player::Player,
+    rules::RuleSet,
+    ruversi::{IllegalMovPolicy, Input, Ruversi, TurnPlayer, IO},
+};
+use ruversi::io::{
+    color_cui::ColorCUI, cui::CUI, json_log::JsonLog, multi::MultiIO, tui::TUI, web::WebUI,
+};
+use ruversi::net::Client;
+use ruversi::player::{computer::Computer, external::External, remote::Remote, user::User};
+use ruversi::record::GameRecord;
+use ruversi::replay::{self, Replay};
+use ruversi::setup::{self, Setup, SetupResult};
+
+const USAGE: &str = "usage: ruversi [UI] [--log FILE] [START] [--rules RULES] [--time CONTROL]
+                       [--illegal-moves LIMIT] [--dark PLAYER] [--light PLAYER]
+       ruversi [UI] [--log FILE] --join HOST:PORT
+       ruversi [--color] --replay FILE
+       ruversi --analyze FILE [--depth N] [--rules RULES] [--json]
+       ruversi --suite FILE [--depth N] [--rules RULES]
+
+UI is one of:
+  --tui           a full-screen terminal UI
+  --color         Unicode disks and ANSI colors (unless NO_COLOR is set)
+  --no-color      Unicode disks without colors
+  --web ADDR      a board in the browser served on ADDR (e.g. 127.0.0.1:8080)
+
+START sets up the position to start from:
+  --size N          the starting position of an N by N board, where N is
+                    even and from 4 to 16 (default 8)
+  --position POS    the squares row by row from a1 in X, O and -, 64 on the
+                    standard board or N*N on an N by N board, and the side
+                    to move, e.g. \"---------------------------OX------XO--------------------------- X\"
+  --turn SIDE       the side to move first, x or o, over the one in POS
+                    (default x)
+  --setup           edit the position interactively before the game
+
+RULES decide the winner of a game played to the end:
+  standard          the player with more disks wins (default)
+  anti              anti-reversi, where the player with fewer disks wins
+
+CONTROL gives each player seconds for the game, and a player out of time
+loses:
+  MAIN              sudden death, e.g. 300
+  MAIN+INC          INC seconds are added after each move, e.g. 300+5
+  MAIN/PERIODxN     then N byo-yomi periods of PERIOD seconds, e.g. 300/30x3
+
+LIMIT is the number of illegal moves a player may make in a turn before it
+loses, e.g. 3, or before a random move is played for it, e.g. 3:random.
+Players are asked again without a limit by default.
+
+--log FILE writes every event of the game to FILE as JSON lines.
+--replay FILE steps through a game in GGF, JSON or a --log file.
+--analyze FILE compares each move of such a game with the best one found by
+searching N plies (default 4), exactly in the endgame, under the rules of the
+game unless --rules is given.
+--suite FILE searches each position of a test suite, one per line in the
+notation of POS and optionally followed by move scores like ; c4:+18; and
+checks the best move found against the best scores.
+
+PLAYER is one of:
+  human           moves are input from the terminal (default)
+  ai[:DEPTH]      the built-in engine searching DEPTH plies (default 4)
+  engine:COMMAND  an external engine speaking the text engine protocol
+  remote:ADDR     a peer joining over TCP, waiting on ADDR (e.g. 0.0.0.0:7777)";
+
+const DEFAULT_DEPTH: u32 = 4;
+const ENGINE_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn make_player<T>(spec: &str, io: &T, time: Option<TimeControl>) -> Result<Box<dyn Player>, String>
+where
+    T: Input + Clone + 'static,
+{
+    match spec.split_once(':') {
+        None if spec == "human" => Ok(Box::new(User::new(Box::new(io.clone())))),
+        None if spec == "ai" => Ok(Box::new(Computer::new(DEFAULT_DEPTH))),
+        Some(("ai", depth)) => depth
+            .parse()
+            .map(|depth| Box::new(Computer::new(depth)) as Box<dyn Player>)
+            .map_err(|_| format!("invalid depth: {}", depth)),
+        Some(("engine", command)) => {
+            let mut words = command.split_whitespace().map(|s| s.to_string());
+            let program = words.next().ok_or("an engine command is required")?;
+            let args: Vec<String> = words.collect();
+            External::spawn(&program, &args, ENGINE_TIMEOUT)
+                .map(|player| match time {
+                    Some(time) => player.with_time_control(time),
+                    None => player,
+                })
+                .map(|player| Box::new(player) as Box<dyn Player>)
+                .map_err(|e| format!("cannot start {}: {}", program, e))
+        }
+        Some(("remote", addr)) => {
+            let listener = TcpListener::bind(addr).map_err(|e| format!("{}: {}", addr, e))?;
+            println!("Waiting for a peer on {} ...", addr);
+            Remote::accept(&listener)
+                .map(|player| Box::new(player) as Box<dyn Player>)
+                .map_err(|e| format!("cannot accept a peer: {}", e))
+        }
+        _ => Err(format!("unknown player: {}", spec)),
+    }
 }
 
-fn main() {
-    let board = init_board();
-    let io = CUI::new();
-    let player_dark = User::new(Box::new(io.clone()));
-    let player_light = User::new(Box::new(io.clone()));
-    let mut ruversi = Ruversi::new(
+fn open_log(args: &[String]) -> Result<Option<Box<dyn IO>>, String> {
+    let rules = rules(args)?.unwrap_or_default();
+    match option_value(args, "--log")? {
+        Some(path) => JsonLog::create(path)
+            .map(|log| Some(Box::new(log.with_rules(rules)) as Box<dyn IO>))
+            .map_err(|e| format!("{}: {}", path, e)),
+        None => Ok(None),
+    }
+}
+
+fn join<T>(io: T, addr: &str, log: Option<Box<dyn IO>>) -> Result<(), String>
+where
+    T: IO + Input + Clone + 'static,
+{
+    let mut client = Client::join(addr).map_err(|e| format!("cannot join {}: {}", addr, e))?;
+    let mut output = MultiIO::new().with(Box::new(io.clone()));
+    if let Some(log) = log {
+        output.push(log);
+    }
+    client
+        .run(&output, &io)
+        .map(|_| ())
+        .map_err(|e| format!("the game was interrupted: {}", e))
+}
+
+struct Start {
+    board: Board,
+    turn: TurnPlayer,
+    time: Option<TimeControl>,
+    illegal_movs: IllegalMovPolicy,
+    rules: RuleSet,
+}
+
+fn play<T>(
+    io: T,
+    start: Start,
+    players: (&str, &str),
+    log: Option<Box<dyn IO>>,
+) -> Result<(), String>
+where
+    T: IO + Input + Clone + 'static,
+{
+    let Start {
         board,
-        Box::new(player_dark),
-        Box::new(player_light),
-        Box::new(io),
-    );
+        turn,
+        time,
+        illegal_movs,
+        rules,
+    } = start;
+    let (dark, light) = players;
+    let player_dark = make_player(dark, &io, time)?;
+    let player_light = make_player(light, &io, time)?;
+    let mut ruversi = Ruversi::new(board, player_dark, player_light, Box::new(io));
+    ruversi.set_first_turn(turn);
+    ruversi.set_rules(rules);
+    if let Some(time) = time {
+        ruversi.set_time_control(time);
+    }
+    ruversi.set_illegal_mov_policy(illegal_movs);
+    if let Some(log) = log {
+        ruversi.add_observer(log);
+    }
 
     ruversi.run();
+    Ok(())
+}
+
+fn option_value<'a>(args: &'a [String], name: &str) -> Result<Option<&'a str>, String> {
+    match args.iter().position(|arg| arg == name) {
+        Some(i) => args
+            .get(i + 1)
+            .map(|value| Some(value.as_str()))
+            .ok_or_else(|| format!("{} requires a value", name)),
+        None => Ok(None),
+    }
+}
+
+fn rules(args: &[String]) -> Result<Option<RuleSet>, String> {
+    option_value(args, "--rules")?
+        .map(|rules| RuleSet::parse(rules).map_err(|msg| format!("--rules: {}", msg)))
+        .transpose()
+}
+
+/// Returns the position to start from, or `None` if the setup mode is left
+/// without playing.
+fn start_position(args: &[String]) -> Result<Option<Start>, String> {
+    let time = match option_value(args, "--time")? {
+        Some(time) => Some(TimeControl::parse(time).map_err(|msg| format!("--time: {}", msg))?),
+        None => None,
+    };
+    let illegal_movs = match option_value(args, "--illegal-moves")? {
+        Some(limit) => {
+            IllegalMovPolicy::parse(limit).map_err(|msg| format!("--illegal-moves: {}", msg))?
+        }
+        None => IllegalMovPolicy::default(),
+    };
+    let rules = rules(args)?.unwrap_or_default();
+    let turn = match option_value(args, "--turn")? {
+        Some(turn) => Some(setup::parse_turn(turn)?),
+        None => None,
+    };
+    let size = match option_value(args, "--size")? {
+        Some(size) => Some(
+            size.parse::<usize>()
+                .map_err(|_| format!("--size: invalid size: {}", size))?,
+        ),
+        None => None,
+    };
+    let BoardState { board, turn } = match (option_value(args, "--position")?, size) {
+        (Some(_), Some(_)) => return Err("--size can not be used with --position".to_string()),
+        (Some(source), None) => {
+            let state = BoardState::parse_or(source, turn.unwrap_or(TurnPlayer::Dark))
+                .map_err(|e| format!("--position: {}", e))?;
+            BoardState::new(state.board, turn.unwrap_or(state.turn))
+        }
+        (None, size) => BoardState::new(
+            Board::initial_with_size(size.unwrap_or(Board::DEFAULT_SIZE))
+                .map_err(|e| format!("--size: {}", e))?,
+            turn.unwrap_or(TurnPlayer::Dark),
+        ),
+    };
+
+    if !args.iter().any(|arg| arg == "--setup") {
+        setup::validate(&board, turn).map_err(|msg| format!("--position: {}", msg))?;
+        return Ok(Some(Start {
+            board,
+            turn,
+            time,
+            illegal_movs,
+            rules,
+        }));
+    }
+
+    let mut setup = Setup::new(board, turn, DEFAULT_DEPTH);
+    setup.set_rules(rules);
+    let stdin = std::io::stdin();
+    let result = setup
+        .run(
+            &ColorCUI::with_color(false),
+            stdin.lock(),
+            std::io::stdout(),
+        )
+        .map_err(|e| e.to_string())?;
+    match result {
+        SetupResult::Play(board, turn) => Ok(Some(Start {
+            board,
+            turn,
+            time,
+            illegal_movs,
+            rules,
+        })),
+        SetupResult::Quit => Ok(None),
+    }
+}
+
+fn start<T>(io: T, args: &[String]) -> Result<(), String>
+where
+    T: IO + Input + Clone + 'static,
+{
+    let log = open_log(args)?;
+    if let Some(addr) = option_value(args, "--join")? {
+        return join(io, addr, log);
+    }
+
+    let dark = option_value(args, "--dark")?.unwrap_or("human");
+    let light = option_value(args, "--light")?.unwrap_or("human");
+    match start_position(args)? {
+        Some(start) => play(io, start, (dark, light), log),
+        None => Ok(()),
+    }
+}
+
+fn load_record(path: &str) -> Result<GameRecord, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    replay::load(&source).map_err(|msg| format!("{}: {}", path, msg))
+}
+
+fn depth(args: &[String]) -> Result<u32, String> {
+    match option_value(args, "--depth")? {
+        Some(depth) => depth
+            .parse()
+            .map_err(|_| format!("invalid depth: {}", depth)),
+        None => Ok(DEFAULT_DEPTH),
+    }
+}
+
+fn analyze(path: &str, args: &[String]) -> Result<(), String> {
+    let depth = depth(args)?;
+    let record = load_record(path)?;
+    let options = AnalysisOptions {
+        depth,
+        rules: rules(args)?.unwrap_or(record.rules),
+        ..AnalysisOptions::default()
+    };
+
+    let analysis = analysis::analyze(&record, options)?;
+    match args.iter().any(|arg| arg == "--json") {
+        true => println!("{}", analysis.to_json()),
+        false => println!("{}", analysis.to_text()),
+    }
+    Ok(())
+}
+
+fn replay(path: &str, color: bool) -> Result<(), String> {
+    let mut replay = Replay::new(load_record(path)?)?;
+
+    let stdin = std::io::stdin();
+    replay::run(
+        &mut replay,
+        &ColorCUI::with_color(color),
+        stdin.lock(),
+        std::io::stdout(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Prints the best move found for each position of a suite, and whether it
+/// has the best of the scores given with the position.
+fn suite(path: &str, args: &[String]) -> Result<(), String> {
+    let depth = depth(args)?;
+    let rules = rules(args)?.unwrap_or_default();
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let entries = notation::parse_suite(&source).map_err(|e| format!("{}: {}", path, e))?;
+
+    let (mut checked, mut found) = (0, 0);
+    for (i, entry) in entries.iter().enumerate() {
+        let ranked = analysis::rank_movs(&entry.state.board, entry.state.turn, depth, rules);
+        let Some((pos, score)) = ranked.first() else {
+            println!("{}: pass", i + 1);
+            continue;
+        };
+        let best = entry.scores.iter().map(|(_, score)| *score).max();
+        let verdict = match best {
+            None => "",
+            Some(best) if entry.scores.contains(&(pos.clone(), best)) => {
+                found += 1;
+                " ok"
+            }
+            Some(_) => " MISS",
+        };
+        checked += best.map_or(0, |_| 1);
+        println!("{}: {} {:+}{}", i + 1, pos.to_notation(), score, verdict);
+    }
+    println!("{} of {} best moves found", found, checked);
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    if let Some(path) = option_value(args, "--analyze")? {
+        analyze(path, args)
+    } else if let Some(path) = option_value(args, "--suite")? {
+        suite(path, args)
+    } else if let Some(path) = option_value(args, "--replay")? {
+        replay(path, args.iter().any(|arg| arg == "--color"))
+    } else if let Some(addr) = option_value(args, "--web")? {
+        let ui = WebUI::bind(addr).map_err(|e| format!("{}: {}", addr, e))?;
+        println!("Open http://{}/ in a browser.", ui.local_addr());
+        start(ui, args)
+    } else if args.iter().any(|arg| arg == "--tui") {
+        let tui = TUI::new();
+        let result = start(tui.clone(), args);
+        // The game has been aborted and the terminal restored by now.
+        if tui.is_quit() {
+            std::process::exit(130);
+        }
+        if tui.is_lost() {
+            std::process::exit(1);
+        }
+        result
+    } else if args.iter().any(|arg| arg == "--no-color") {
+        start(ColorCUI::with_color(false), args)
+    } else if args.iter().any(|arg| arg == "--color") {
+        start(ColorCUI::new(), args)
+    } else {
+        start(CUI::new(), args)
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if let Err(msg) = run(&args) {
+        eprintln!("ruversi: {}\n\n{}", msg, USAGE);
+        std::process::exit(2);
+    }
 }
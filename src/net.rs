@@ -0,0 +1,337 @@
+//! A line protocol for playing a game with a remote peer over TCP.
+//!
+//! The host runs the game and the peer takes part as one of its players.
+//! Every message is one line of space separated words.
+//!
+//! ```text
+//! peer -> host   HELLO ruversi <version>     handshake, sent first
+//! host -> peer   WELCOME <dark|light>        the color the peer plays
+//! host -> peer   BOARD <N*N chars of x/o/_>  the whole board, rows from the top
+//! host -> peer   PLAYED <dark|light> <d3>    a move has been made by either side
+//! host -> peer   YOURMOVE [<time left>]      the peer has to answer with MOVE
+//! peer -> host   MOVE <d3>                   the peer's move
+//! host -> peer   ILLEGAL <d3>                the move was rejected, YOURMOVE follows
+//! host -> peer   END <result>                the game has ended, see below
+//! either         BYE                         the sender is leaving
+//! ```
+//!
+//! The host sends `WELCOME` once the game starts, as the side of the peer
+//! is known only then. It sends `BOARD` after it and after every move, so
+//! the peer never has to replay moves by itself. `END` carries the whole
+//! `GameResult` as JSON without spaces, such as
+//! `{"light_disks":2,"dark_disks":3,"winner":"light","outcome":{"resignation":"dark"}}`,
+//! so a game ended otherwise than by counting the disks reaches the peer as
+//! it is. Under a time control, `YOURMOVE` has the time the peer had left
+//! at the start of the turn as `<main ms> <period ms> <periods>`, the
+//! byo-yomi period being 0 without byo-yomi. A peer which disconnects or
+//! sends something unexpected is replaced by the first legal move on the
+//! host side.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::core::board::{Board, PlaceError, Position};
+use crate::core::clock::TimeLeft;
+use crate::core::player::Action;
+use crate::core::ruversi::{GameResult, Input, Retry, TurnPlayer, IO};
+
+pub const PROTOCOL_VERSION: u32 = 2;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Message {
+    Hello(u32),
+    Welcome(TurnPlayer),
+    Board(Board),
+    Played(TurnPlayer, Position),
+    YourMove(Option<TimeLeft>),
+    Move(Position),
+    Illegal(Position),
+    End(GameResult),
+    Bye,
+}
+
+fn color_name(turn: &TurnPlayer) -> &'static str {
+    match turn {
+        TurnPlayer::Dark => "dark",
+        TurnPlayer::Light => "light",
+    }
+}
+
+fn parse_color(source: &str) -> Result<TurnPlayer, String> {
+    match source {
+        "dark" => Ok(TurnPlayer::Dark),
+        "light" => Ok(TurnPlayer::Light),
+        _ => Err(format!("invalid color: {}", source)),
+    }
+}
+
+fn parse_pos(source: &str) -> Result<Position, String> {
+    Position::try_from_notation(source).map_err(|msg| format!("{}: {}", msg, source))
+}
+
+fn parse_num<T: std::str::FromStr>(source: &str) -> Result<T, String> {
+    source
+        .parse()
+        .map_err(|_| format!("invalid number: {}", source))
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hello(version) => write!(f, "HELLO ruversi {}", version),
+            Self::Welcome(turn) => write!(f, "WELCOME {}", color_name(turn)),
+            Self::Board(board) => write!(f, "BOARD {}", board.to_compact_str()),
+            Self::Played(turn, pos) => {
+                write!(f, "PLAYED {} {}", color_name(turn), pos.to_notation())
+            }
+            Self::YourMove(None) => write!(f, "YOURMOVE"),
+            Self::YourMove(Some(time_left)) => write!(
+                f,
+                "YOURMOVE {} {} {}",
+                time_left.main.as_millis(),
+                time_left.period.as_millis(),
+                time_left.periods
+            ),
+            Self::Move(pos) => write!(f, "MOVE {}", pos.to_notation()),
+            Self::Illegal(pos) => write!(f, "ILLEGAL {}", pos.to_notation()),
+            Self::End(result) => write!(
+                f,
+                "END {}",
+                serde_json::to_string(result).map_err(|_| fmt::Error)?
+            ),
+            Self::Bye => write!(f, "BYE"),
+        }
+    }
+}
+
+impl Message {
+    pub fn try_from_str(source: &str) -> Result<Self, String> {
+        let words: Vec<&str> = source.split_whitespace().collect();
+        let message = match words.as_slice() {
+            ["HELLO", "ruversi", version] => Self::Hello(parse_num(version)?),
+            ["WELCOME", color] => Self::Welcome(parse_color(color)?),
+            ["BOARD", board] => Self::Board(
+                Board::try_from_str(board).map_err(|msg| format!("invalid board: {}", msg))?,
+            ),
+            ["PLAYED", color, pos] => Self::Played(parse_color(color)?, parse_pos(pos)?),
+            ["YOURMOVE"] => Self::YourMove(None),
+            ["YOURMOVE", main, period, periods] => Self::YourMove(Some(TimeLeft {
+                main: Duration::from_millis(parse_num(main)?),
+                period: Duration::from_millis(parse_num(period)?),
+                periods: parse_num(periods)?,
+            })),
+            ["MOVE", pos] => Self::Move(parse_pos(pos)?),
+            ["ILLEGAL", pos] => Self::Illegal(parse_pos(pos)?),
+            ["END", result] => Self::End(
+                serde_json::from_str(result).map_err(|e| format!("invalid result: {}", e))?,
+            ),
+            ["BYE"] => Self::Bye,
+            _ => return Err(format!("unexpected message: {}", source)),
+        };
+
+        Ok(message)
+    }
+}
+
+/// One end of a connection exchanging `Message`s.
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        let writer = stream.try_clone()?;
+        Ok(Self {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{}", message)?;
+        self.writer.flush()
+    }
+
+    /// Receives the next message. A closed connection is reported as
+    /// `ErrorKind::UnexpectedEof`.
+    pub fn recv(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the connection is closed",
+            ));
+        }
+
+        Message::try_from_str(line.trim())
+            .map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))
+    }
+}
+
+/// The peer side of a networked game. The game itself runs on the host; the
+/// client only mirrors it to an `IO` and answers move requests with an
+/// `Input`.
+pub struct Client {
+    conn: Connection,
+    turn: TurnPlayer,
+    left: bool,
+}
+
+impl Client {
+    /// Connects to a host and performs the handshake.
+    pub fn join<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let mut conn = Connection::new(TcpStream::connect(addr)?)?;
+        conn.send(&Message::Hello(PROTOCOL_VERSION))?;
+
+        match conn.recv()? {
+            Message::Welcome(turn) => Ok(Self {
+                conn,
+                turn,
+                left: false,
+            }),
+            message => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected WELCOME, got {}", message),
+            )),
+        }
+    }
+
+    fn leave(&mut self) {
+        if !self.left {
+            self.left = true;
+            let _ = self.conn.send(&Message::Bye);
+        }
+    }
+
+    /// The color this client plays.
+    pub fn turn(&self) -> TurnPlayer {
+        self.turn
+    }
+
+    /// Follows the game until it ends. Returns an error if the host
+    /// disconnects before the end, or if the input aborts and the game is
+    /// left. Other actions than a move or an abort are not sent.
+    pub fn run(&mut self, io: &dyn IO, input: &dyn Input) -> io::Result<GameResult> {
+        let mut board: Option<Board> = None;
+        let mut retrying = false;
+
+        loop {
+            match self.conn.recv()? {
+                Message::Board(new_board) => {
+                    match board {
+                        None => io.game_start(&new_board),
+                        Some(_) => io.after_update(&new_board),
+                    }
+                    board = Some(new_board);
+                }
+                Message::Played(turn, pos) => io.after_mov(&pos, &turn),
+                Message::YourMove(time_left) => {
+                    let current = board.clone().unwrap_or_default();
+                    if !retrying {
+                        io.start_turn(&self.turn, time_left);
+                    }
+                    retrying = false;
+                    io.before_mov(&current, &self.turn);
+                    let pos = loop {
+                        match input.input_action() {
+                            Action::Move(pos) => break pos,
+                            Action::Abort => {
+                                self.leave();
+                                return Err(io::Error::new(
+                                    io::ErrorKind::Interrupted,
+                                    "the game was left",
+                                ));
+                            }
+                            _ => (),
+                        }
+                    };
+                    self.conn.send(&Message::Move(pos))?;
+                }
+                Message::Illegal(pos) => {
+                    retrying = true;
+                    // The host only tells the square, so the reason is found
+                    // on the board last sent.
+                    let error = board
+                        .clone()
+                        .unwrap_or_default()
+                        .count_turn_disks(pos.clone(), self.turn.into_disk())
+                        .err()
+                        .unwrap_or(PlaceError::NothingToTurn(pos));
+                    io.after_illegal_mov(&error, &self.turn, Retry::Again(None));
+                }
+                Message::End(result) => {
+                    io.game_end(&board.unwrap_or_default(), &result);
+                    self.leave();
+                    return Ok(result);
+                }
+                Message::Bye => {
+                    self.left = true;
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "the host left the game",
+                    ));
+                }
+                message => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unexpected message: {}", message),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.leave();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rules::RuleSet;
+    use crate::core::ruversi::Outcome;
+
+    #[test]
+    fn test_message() {
+        let messages = [
+            Message::Hello(1),
+            Message::Welcome(TurnPlayer::Light),
+            Message::Board(Board::initial()),
+            Message::Played(TurnPlayer::Dark, Position::new(3, 2)),
+            Message::YourMove(None),
+            Message::YourMove(Some(TimeLeft {
+                main: Duration::from_millis(299_500),
+                period: Duration::from_secs(30),
+                periods: 3,
+            })),
+            Message::Move(Position::new(2, 4)),
+            Message::Illegal(Position::new(0, 0)),
+            Message::End(GameResult::new(RuleSet::Standard, 24, 40)),
+            Message::End(GameResult::with_outcome(
+                Outcome::Resignation(TurnPlayer::Dark),
+                RuleSet::AntiReversi,
+                2,
+                3,
+            )),
+            Message::Bye,
+        ];
+        for message in messages {
+            assert_eq!(Message::try_from_str(&message.to_string()), Ok(message));
+        }
+
+        assert!(Message::try_from_str("MOVE z9").is_err());
+        assert!(Message::try_from_str("HELLO").is_err());
+        assert!(Message::try_from_str("YOURMOVE 300").is_err());
+        assert!(Message::try_from_str("END 40 24").is_err());
+        assert!(
+            Message::try_from_str(r#"END {"light_disks":2,"dark_disks":3,"winner":"light"}"#)
+                .is_err()
+        );
+    }
+}
@@ -1 +1,4 @@
+pub mod computer;
+pub mod external;
+pub mod remote;
 pub mod user;
@@ -0,0 +1,63 @@
+use crate::ai;
+use crate::core::{
+    player::{Action, MoveContext, Player},
+    ruversi::TurnPlayer,
+};
+
+/// A player which chooses its moves by the alpha-beta search of `ai`, for
+/// whichever side it is given.
+pub struct Computer {
+    depth: u32,
+}
+
+impl Player for Computer {
+    fn mov(&mut self, ctx: &MoveContext) -> Action {
+        ai::search(ctx.board, ctx.side.into_disk(), self.depth, ctx.rules)
+            .map(|(pos, _)| Action::Move(pos))
+            .expect("mov must be called only when a legal move exists.")
+    }
+
+    /// Accepts a draw unless the search expects to win under the rules of
+    /// the game. The board is searched for the other player, who offers
+    /// the draw on its turn, and the score is turned to the view of this
+    /// player.
+    fn accept_draw(&mut self, ctx: &MoveContext) -> bool {
+        let offerer = TurnPlayer::other(&ctx.side).into_disk();
+        let score = match ai::search(ctx.board, offerer, self.depth, ctx.rules) {
+            Some((_, score)) => -score,
+            None => ai::search(ctx.board, ctx.side.into_disk(), self.depth, ctx.rules)
+                .map_or(0, |(_, score)| score),
+        };
+        score <= 0
+    }
+}
+
+impl Computer {
+    pub fn new(depth: u32) -> Self {
+        Self { depth }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::Board;
+    use crate::core::rules::RuleSet;
+
+    #[test]
+    fn test_accept_draw() {
+        // Light is to move, offers a draw and can only take back a few disks.
+        let board = Board::try_from_str("oxxxxxxxxxxxxxx_").unwrap();
+        let mut ctx = MoveContext {
+            board: &board,
+            side: TurnPlayer::Dark,
+            time_left: None,
+            rules: RuleSet::Standard,
+        };
+        let mut computer = Computer::new(2);
+        assert!(!computer.accept_draw(&ctx));
+
+        ctx.rules = RuleSet::AntiReversi;
+        assert!(computer.accept_draw(&ctx));
+    }
+}
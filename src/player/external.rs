@@ -0,0 +1,555 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command as Process, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::{
+    board::{Board, Position},
+    clock::{TimeControl, TimeLeft},
+    player::{Action, MoveContext, Player},
+    rules::RuleSet,
+    ruversi::TurnPlayer,
+};
+use crate::protocol::command::{Command, Vertex};
+
+/// A player which runs an engine speaking the text engine protocol in a
+/// child process.
+///
+/// `new_game` and `on_undo` are forwarded as `clear_board`/`setboard`,
+/// `on_move` and `on_pass` as `play`, and `mov` as `genmove`. A game under
+/// other rules than the engine has been told of is preceded by `rules`.
+/// Under a time control, every game is followed by `time_settings` and
+/// every `genmove` is preceded by `time_left`. If
+/// the engine crashes, times out or answers with something unusable, it is
+/// shut down and the player falls back to the first legal move for the rest
+/// of the game.
+pub struct External {
+    /// The side given by `new_game`, dark until then.
+    side: TurnPlayer,
+    board: Board,
+    timeout: Duration,
+    /// The rules the engine has been told, standard until then.
+    rules: RuleSet,
+    time_control: Option<TimeControl>,
+    conn: Option<Connection>,
+}
+
+struct Connection {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u32,
+    /// A move generated by the engine and not yet confirmed by `on_move`.
+    pending: Option<Position>,
+}
+
+impl Connection {
+    fn spawn(program: &str, args: &[String]) -> io::Result<Self> {
+        let mut child = Process::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin must be piped.");
+        let stdout = child.stdout.take().expect("stdout must be piped.");
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+            next_id: 1,
+            pending: None,
+        })
+    }
+
+    /// Sends a command and waits for its response for at most `timeout`.
+    fn request(&mut self, command: &Command, timeout: Duration) -> Result<String, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        writeln!(self.stdin, "{} {}", id, command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("cannot send `{}`: {}", command, e))?;
+
+        let deadline = Instant::now() + timeout;
+        let mut response: Option<Result<String, String>> = None;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(format!("no response to `{}` in {:?}", command, timeout))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!("the engine exited during `{}`", command))
+                }
+            };
+            let line = line.trim_end();
+
+            match response.as_mut() {
+                None if line.is_empty() => (),
+                None => response = Some(Self::parse_status(line, id)?),
+                Some(_) if line.is_empty() => break,
+                Some(Ok(text)) | Some(Err(text)) => {
+                    text.push('\n');
+                    text.push_str(line);
+                }
+            }
+        }
+
+        response
+            .expect("A response must be read before the blank line.")
+            .map_err(|e| format!("`{}` failed: {}", command, e))
+    }
+
+    fn parse_status(line: &str, id: u32) -> Result<Result<String, String>, String> {
+        let ok = match line.chars().next() {
+            Some('=') => true,
+            Some('?') => false,
+            _ => return Err(format!("malformed response: {}", line)),
+        };
+
+        let rest = &line[1..];
+        let text = match rest.split_once(char::is_whitespace) {
+            Some((head, text)) if head == id.to_string() => text.trim(),
+            None if rest == id.to_string() => "",
+            _ => rest.trim(),
+        };
+
+        Ok(if ok {
+            Ok(text.to_string())
+        } else {
+            Err(text.to_string())
+        })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.stdin.flush();
+        let deadline = Instant::now() + Duration::from_millis(200);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Player for External {
+    fn new_game(&mut self, board: &Board, side: TurnPlayer, rules: RuleSet) {
+        self.side = side;
+        // Engines which know only the standard rules are never asked about
+        // the rules.
+        if rules != self.rules {
+            self.rules = rules;
+            self.request(&Command::Rules(rules));
+        }
+        self.set_board(board);
+        if let Some(control) = self.time_control {
+            self.request(&time_settings(control));
+        }
+    }
+
+    fn on_move(&mut self, pos: &Position, turn: TurnPlayer) {
+        self.board
+            .place(pos.clone(), turn.into_disk())
+            .expect("A disk must be able to place on the pos.");
+
+        let pending = self.conn.as_mut().and_then(|conn| conn.pending.take());
+        let confirmed = pending
+            .as_ref()
+            .is_some_and(|pending| *pending == *pos && turn == self.side);
+        if !confirmed {
+            // The engine has played the generated move on its own board, so
+            // another move played instead has to replace it.
+            if pending.is_some() {
+                self.request(&Command::Undo);
+            }
+            self.request(&Command::Play(turn, Vertex::Move(pos.clone())));
+        }
+    }
+
+    fn on_pass(&mut self, turn: TurnPlayer) {
+        self.request(&Command::Play(turn, Vertex::Pass));
+    }
+
+    fn on_undo(&mut self, board: &Board) {
+        self.set_board(board);
+    }
+
+    fn mov(&mut self, ctx: &MoveContext) -> Action {
+        self.side = ctx.side;
+        if let Some(time_left) = ctx.time_left {
+            self.request(&time_left_command(ctx.side, time_left));
+        }
+        Action::Move(self.genmove())
+    }
+}
+
+impl External {
+    /// Spawns `program` with `args` to play with it. Every request to the
+    /// engine has to be answered within `timeout`.
+    pub fn spawn(program: &str, args: &[String], timeout: Duration) -> io::Result<Self> {
+        let conn = Connection::spawn(program, args)?;
+        let mut player = Self {
+            side: TurnPlayer::Dark,
+            board: Board::new(),
+            timeout,
+            rules: RuleSet::Standard,
+            time_control: None,
+            conn: Some(conn),
+        };
+
+        match player.request(&Command::ProtocolVersion) {
+            Some(_) => Ok(player),
+            None => Err(io::Error::other(format!(
+                "{} does not speak the engine protocol",
+                program
+            ))),
+        }
+    }
+
+    /// Tells the engine `control` at the start of every game, and the time
+    /// left before every move.
+    pub fn with_time_control(mut self, control: TimeControl) -> Self {
+        self.time_control = Some(control);
+        self
+    }
+
+    /// Returns whether the engine is still running and answering.
+    pub fn is_alive(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    fn set_board(&mut self, board: &Board) {
+        let command = if *board == Board::initial() {
+            Command::ClearBoard
+        } else {
+            Command::SetBoard(board.clone())
+        };
+        self.board = board.clone();
+        // A generated move is gone with the board it was played on.
+        if let Some(conn) = self.conn.as_mut() {
+            conn.pending = None;
+        }
+        self.request(&command);
+    }
+
+    fn request(&mut self, command: &Command) -> Option<String> {
+        let result = self.conn.as_mut()?.request(command, self.timeout);
+
+        match result {
+            Ok(text) => Some(text),
+            Err(msg) => {
+                self.shutdown(&msg);
+                None
+            }
+        }
+    }
+
+    fn shutdown(&mut self, reason: &str) {
+        if self.conn.take().is_some() {
+            eprintln!("external engine ({}) stopped: {}", self.side, reason);
+        }
+    }
+
+    /// Asks the engine for a move, or falls back to the first legal move
+    /// if it fails.
+    fn genmove(&mut self) -> Position {
+        // A generated move which was not accepted has to be taken back
+        // before asking again.
+        let rejected = self.conn.as_mut().and_then(|conn| conn.pending.take());
+        if rejected.is_some() {
+            self.request(&Command::Undo);
+        }
+
+        let response = self.request(&Command::GenMove(self.side));
+        let pos = match response.as_deref().map(Vertex::try_from_str) {
+            Some(Ok(Vertex::Move(pos))) => pos,
+            Some(Ok(Vertex::Pass)) | Some(Err(_)) => {
+                self.shutdown(&format!(
+                    "unusable move from the engine: {}",
+                    response.unwrap_or_default()
+                ));
+                return self.fallback_mov();
+            }
+            None => return self.fallback_mov(),
+        };
+
+        if let Some(conn) = self.conn.as_mut() {
+            conn.pending = Some(pos.clone());
+        }
+        pos
+    }
+
+    fn fallback_mov(&self) -> Position {
+        self.board
+            .legal_movs(self.side.into_disk())
+            .into_iter()
+            .next()
+            .expect("mov must be called only when a legal move exists.")
+    }
+}
+
+/// GTP has no increment, which is left out, and its byo-yomi periods are
+/// Canadian, so a byo-yomi period is told as a period of one move.
+fn time_settings(control: TimeControl) -> Command {
+    let (main, period) = match control {
+        TimeControl::SuddenDeath { main } | TimeControl::Increment { main, .. } => {
+            (main, Duration::ZERO)
+        }
+        TimeControl::ByoYomi { main, period, .. } => (main, period),
+    };
+    Command::TimeSettings {
+        main_time: main.as_secs(),
+        byo_yomi_time: period.as_secs(),
+        byo_yomi_stones: u64::from(period > Duration::ZERO),
+    }
+}
+
+/// The main time left, or the byo-yomi period for one move once it has run
+/// out.
+fn time_left_command(turn: TurnPlayer, time_left: TimeLeft) -> Command {
+    match time_left.main > Duration::ZERO || time_left.periods == 0 {
+        true => Command::TimeLeft(turn, time_left.main.as_secs(), 0),
+        false => Command::TimeLeft(turn, time_left.period.as_secs(), 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::core::board::{Disk, PlaceError};
+    use crate::core::ruversi::{Fallback, GameResult, IllegalMovPolicy, Retry, Ruversi, IO};
+    use crate::player::computer::Computer;
+
+    /// Plays `d4`, which is never empty, and keeps it on its board until
+    /// it is undone, a move is played over it or the board is reset.
+    const ILLEGAL_ENGINE: &str = r#"pending=
+        while read id cmd rest; do
+            case "$cmd" in
+                genmove) pending=1; echo "=$id d4" ;;
+                undo) [ -n "$pending" ] && echo "=$id" || echo "?$id cannot undo"; pending= ;;
+                play) [ -z "$pending" ] && echo "=$id" || echo "?$id illegal move" ;;
+                *) pending=; echo "=$id" ;;
+            esac
+            echo
+        done"#;
+
+    /// Counts the illegal moves followed by a random move.
+    struct RandomMovs(Rc<Cell<u32>>);
+
+    impl IO for RandomMovs {
+        fn game_start(&self, _board: &Board) {}
+        fn skip_turn(&self, _turn: &TurnPlayer) {}
+        fn start_turn(&self, _turn: &TurnPlayer, _time_left: Option<TimeLeft>) {}
+        fn before_mov(&self, _board: &Board, _turn: &TurnPlayer) {}
+        fn after_illegal_mov(&self, _error: &PlaceError, _turn: &TurnPlayer, retry: Retry) {
+            if retry == Retry::RandomMove {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+        fn after_mov(&self, _pos: &Position, _turn: &TurnPlayer) {}
+        fn after_update(&self, _board: &Board) {}
+        fn game_end(&self, _board: &Board, _result: &GameResult) {}
+    }
+
+    fn sh(script: &str) -> io::Result<External> {
+        External::spawn(
+            "sh",
+            &["-c".to_string(), script.to_string()],
+            Duration::from_millis(500),
+        )
+    }
+
+    #[test]
+    fn test_mov() {
+        let script = r#"while read id cmd rest; do
+            case "$cmd" in
+                genmove) echo "=$id f5" ;;
+                *) echo "=$id" ;;
+            esac
+            echo
+        done"#;
+        let mut player = sh(script).unwrap();
+        let board = Board::initial();
+        player.new_game(&board, TurnPlayer::Dark, RuleSet::Standard);
+
+        let ctx = MoveContext {
+            board: &board,
+            side: TurnPlayer::Dark,
+            time_left: None,
+            rules: RuleSet::Standard,
+        };
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(5, 4)));
+        player.on_move(&Position::new(5, 4), TurnPlayer::Dark);
+        assert!(player.is_alive());
+    }
+
+    #[test]
+    fn test_side() {
+        let script = r#"while read id cmd rest; do
+            case "$cmd" in
+                genmove) [ "$rest" = white ] && echo "=$id c5" || echo "=$id f5" ;;
+                *) echo "=$id" ;;
+            esac
+            echo
+        done"#;
+        let mut player = sh(script).unwrap();
+        let mut board = Board::initial();
+        board.place(Position::new(3, 2), Disk::Dark).unwrap();
+        player.new_game(&board, TurnPlayer::Light, RuleSet::Standard);
+
+        let ctx = MoveContext {
+            board: &board,
+            side: TurnPlayer::Light,
+            time_left: None,
+            rules: RuleSet::Standard,
+        };
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(2, 4)));
+        player.on_move(&Position::new(2, 4), TurnPlayer::Light);
+        assert!(player.is_alive());
+    }
+
+    #[test]
+    fn test_rules() {
+        let script = r#"while read id cmd rest; do
+            case "$cmd" in
+                rules) rules="$rest"; echo "=$id" ;;
+                genmove) [ "$rules" = anti-reversi ] && echo "=$id d3" || echo "=$id f5" ;;
+                *) echo "=$id" ;;
+            esac
+            echo
+        done"#;
+        let mut player = sh(script).unwrap();
+        let board = Board::initial();
+        player.new_game(&board, TurnPlayer::Dark, RuleSet::AntiReversi);
+
+        let ctx = MoveContext {
+            board: &board,
+            side: TurnPlayer::Dark,
+            time_left: None,
+            rules: RuleSet::AntiReversi,
+        };
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(3, 2)));
+        assert!(player.is_alive());
+    }
+
+    #[test]
+    fn test_time() {
+        let script = r#"while read id cmd rest; do
+            case "$cmd" in
+                time_settings) settings="$rest"; echo "=$id" ;;
+                time_left) left="$rest"; echo "=$id" ;;
+                genmove) [ "$settings $left" = "300 30 1 black 30 1" ] && echo "=$id f5" || echo "=$id c4" ;;
+                *) echo "=$id" ;;
+            esac
+            echo
+        done"#;
+        let control = TimeControl::parse("300/30x3").unwrap();
+        let mut player = sh(script).unwrap().with_time_control(control);
+        let board = Board::initial();
+        player.new_game(&board, TurnPlayer::Dark, RuleSet::Standard);
+
+        let ctx = MoveContext {
+            board: &board,
+            side: TurnPlayer::Dark,
+            time_left: Some(TimeLeft {
+                main: Duration::ZERO,
+                period: Duration::from_secs(30),
+                periods: 2,
+            }),
+            rules: RuleSet::Standard,
+        };
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(5, 4)));
+        assert!(player.is_alive());
+    }
+
+    #[test]
+    fn test_random_mov_after_illegal() {
+        let player = sh(ILLEGAL_ENGINE).unwrap();
+        let random_movs = Rc::new(Cell::new(0));
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(player),
+            Box::new(Computer::new(1)),
+            Box::new(RandomMovs(Rc::clone(&random_movs))),
+        );
+        ruversi.set_illegal_mov_policy(IllegalMovPolicy {
+            max_attempts: Some(1),
+            fallback: Fallback::RandomMove,
+        });
+        ruversi.run();
+
+        // An engine shut down after the first turn would fall back to legal
+        // moves without asking it.
+        assert!(random_movs.get() > 1);
+    }
+
+    #[test]
+    fn test_new_game_after_rejected_mov() {
+        let mut player = sh(ILLEGAL_ENGINE).unwrap();
+        let board = Board::initial();
+        let ctx = MoveContext {
+            board: &board,
+            side: TurnPlayer::Dark,
+            time_left: None,
+            rules: RuleSet::Standard,
+        };
+
+        // The game is forfeited on the rejected move, and another starts.
+        player.new_game(&board, TurnPlayer::Dark, RuleSet::Standard);
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(3, 3)));
+        player.new_game(&board, TurnPlayer::Dark, RuleSet::Standard);
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(3, 3)));
+        assert!(player.is_alive());
+
+        player.on_undo(&board);
+        assert_eq!(player.mov(&ctx), Action::Move(Position::new(3, 3)));
+        assert!(player.is_alive());
+    }
+
+    #[test]
+    fn test_crash_falls_back() {
+        let script = r#"read id cmd; echo "=$id 2"; echo; read line; exit 1"#;
+        let mut player = sh(script).unwrap();
+        player.new_game(&Board::initial(), TurnPlayer::Dark, RuleSet::Standard);
+
+        let pos = player.genmove();
+        assert!(!player.is_alive());
+        assert!(Board::initial().can_place(pos, Disk::Dark));
+    }
+
+    #[test]
+    fn test_timeout_falls_back() {
+        let script = r#"read id cmd; echo "=$id 2"; echo; sleep 5"#;
+        let mut player = sh(script).unwrap();
+        player.new_game(&Board::initial(), TurnPlayer::Dark, RuleSet::Standard);
+
+        assert!(!player.is_alive());
+        let pos = player.genmove();
+        assert!(Board::initial().can_place(pos, Disk::Dark));
+    }
+
+    #[test]
+    fn test_not_an_engine() {
+        assert!(sh("echo hello").is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use std::io;
+use std::net::TcpListener;
+
+use crate::core::{
+    board::{Board, Position},
+    player::{Action, MoveContext, Player},
+    rules::RuleSet,
+    ruversi::{GameResult, TurnPlayer},
+};
+use crate::net::{Connection, Message, PROTOCOL_VERSION};
+
+/// A player on another machine, connected over TCP with the protocol of
+/// `net`. The peer is welcomed with its side when the game starts. If the
+/// peer disconnects, the player falls back to the first legal move for the
+/// rest of the game.
+pub struct Remote {
+    /// The side given by `new_game`, `None` before the peer is welcomed.
+    side: Option<TurnPlayer>,
+    board: Board,
+    conn: Option<Connection>,
+    /// A move sent by the peer and not yet confirmed by `on_move`.
+    pending: Option<Position>,
+}
+
+impl Player for Remote {
+    fn new_game(&mut self, board: &Board, side: TurnPlayer, _rules: RuleSet) {
+        if self.side.replace(side).is_none() {
+            self.send(&Message::Welcome(side));
+        }
+        self.board = board.clone();
+        self.send(&Message::Board(self.board.clone()));
+    }
+
+    fn on_move(&mut self, pos: &Position, turn: TurnPlayer) {
+        self.board
+            .place(pos.clone(), turn.into_disk())
+            .expect("A disk must be able to place on the pos.");
+        self.pending = None;
+
+        self.send(&Message::Played(turn, pos.clone()));
+        self.send(&Message::Board(self.board.clone()));
+    }
+
+    fn on_undo(&mut self, board: &Board) {
+        self.board = board.clone();
+        self.send(&Message::Board(self.board.clone()));
+    }
+
+    fn on_game_end(&mut self, _board: &Board, result: &GameResult) {
+        self.send(&Message::End(result.clone()));
+    }
+
+    fn mov(&mut self, ctx: &MoveContext) -> Action {
+        Action::Move(self.receive_mov(ctx))
+    }
+}
+
+impl Remote {
+    /// Waits for a peer on `listener` and performs the handshake up to
+    /// `WELCOME`, which is sent by `new_game`.
+    pub fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        let mut conn = Connection::new(stream)?;
+
+        match conn.recv()? {
+            Message::Hello(PROTOCOL_VERSION) => (),
+            message => {
+                let _ = conn.send(&Message::Bye);
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "expected HELLO ruversi {}, got {}",
+                        PROTOCOL_VERSION, message
+                    ),
+                ));
+            }
+        }
+
+        Ok(Self {
+            side: None,
+            board: Board::new(),
+            conn: Some(conn),
+            pending: None,
+        })
+    }
+
+    /// Returns whether the peer is still connected.
+    pub fn is_connected(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    fn send(&mut self, message: &Message) {
+        let result = match self.conn.as_mut() {
+            Some(conn) => conn.send(message),
+            None => return,
+        };
+
+        if let Err(e) = result {
+            self.disconnect(&e.to_string());
+        }
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        if let Some(mut conn) = self.conn.take() {
+            let _ = conn.send(&Message::Bye);
+            match self.side {
+                Some(side) => eprintln!("remote player ({}) disconnected: {}", side, reason),
+                None => eprintln!("remote player disconnected: {}", reason),
+            }
+        }
+    }
+
+    /// Asks the peer for a move with the time it has left, or falls back to
+    /// the first legal move if it has gone.
+    fn receive_mov(&mut self, ctx: &MoveContext) -> Position {
+        let (board, side) = (ctx.board, ctx.side);
+        if let Some(rejected) = self.pending.take() {
+            self.send(&Message::Illegal(rejected));
+        }
+        self.send(&Message::YourMove(ctx.time_left));
+
+        let received = match self.conn.as_mut() {
+            Some(conn) => conn.recv(),
+            None => return Self::fallback_mov(board, side),
+        };
+
+        match received {
+            Ok(Message::Move(pos)) => {
+                self.pending = Some(pos.clone());
+                pos
+            }
+            Ok(message) => {
+                self.disconnect(&format!("unexpected message: {}", message));
+                Self::fallback_mov(board, side)
+            }
+            Err(e) => {
+                self.disconnect(&e.to_string());
+                Self::fallback_mov(board, side)
+            }
+        }
+    }
+
+    fn fallback_mov(board: &Board, side: TurnPlayer) -> Position {
+        board
+            .legal_movs(side.into_disk())
+            .into_iter()
+            .next()
+            .expect("mov must be called only when a legal move exists.")
+    }
+}
+
+impl Drop for Remote {
+    fn drop(&mut self) {
+        self.send(&Message::Bye);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::core::board::{Disk, PlaceError};
+    use crate::core::clock::{TimeControl, TimeLeft};
+    use crate::core::ruversi::{Input, Outcome, Retry, Ruversi, IO};
+    use crate::net::Client;
+    use crate::player::computer::Computer;
+
+    /// Ignores every event except the board and the time left, and inputs the first legal
+    /// move after an illegal one.
+    #[derive(Default)]
+    struct Bot {
+        board: RefCell<Board>,
+        turn: RefCell<Option<TurnPlayer>>,
+        tried_illegal: Cell<bool>,
+        time_left: Cell<Option<TimeLeft>>,
+    }
+
+    impl IO for Bot {
+        fn game_start(&self, board: &Board) {
+            self.board.replace(board.clone());
+        }
+        fn skip_turn(&self, _turn: &TurnPlayer) {}
+        fn start_turn(&self, _turn: &TurnPlayer, time_left: Option<TimeLeft>) {
+            self.time_left.set(time_left);
+        }
+        fn before_mov(&self, board: &Board, turn: &TurnPlayer) {
+            self.board.replace(board.clone());
+            self.turn.replace(Some(*turn));
+        }
+        fn after_illegal_mov(&self, _error: &PlaceError, _turn: &TurnPlayer, _retry: Retry) {}
+        fn after_mov(&self, _pos: &Position, _turn: &TurnPlayer) {}
+        fn after_update(&self, _board: &Board) {}
+        fn game_end(&self, _board: &Board, _result: &GameResult) {}
+    }
+
+    impl Input for Bot {
+        fn input_pos(&self) -> Position {
+            if !self.tried_illegal.replace(true) {
+                return Position::new(0, 0);
+            }
+            let disk = self.turn.borrow().unwrap().into_disk();
+            self.board.borrow().legal_movs(disk).remove(0)
+        }
+    }
+
+    /// Resigns on its first turn.
+    struct Resigner;
+
+    impl Player for Resigner {
+        fn mov(&mut self, _ctx: &MoveContext) -> Action {
+            Action::Resign
+        }
+    }
+
+    fn play_against(dark: Box<dyn Player>, remote: Remote) -> (Board, GameResult) {
+        let board = Board::initial();
+        let mut ruversi = Ruversi::new(board, dark, Box::new(remote), Box::new(Bot::default()));
+        let result = ruversi.run();
+        (ruversi.board().clone(), result)
+    }
+
+    fn play(remote: Remote) -> Board {
+        play_against(Box::new(Computer::new(1)), remote).0
+    }
+
+    #[test]
+    fn test_game_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut client = Client::join(addr).unwrap();
+            assert_eq!(client.turn(), TurnPlayer::Light);
+            let bot = Bot::default();
+            client.run(&bot, &bot)
+        });
+
+        let remote = Remote::accept(&listener).unwrap();
+        let board = play(remote);
+
+        let result = client.join().unwrap().unwrap();
+        assert_eq!(result.dark_disks, board.count_disks(&Disk::Dark));
+        assert_eq!(result.light_disks, board.count_disks(&Disk::Light));
+    }
+
+    #[test]
+    fn test_resignation_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut client = Client::join(addr).unwrap();
+            let bot = Bot::default();
+            client.run(&bot, &bot)
+        });
+
+        let remote = Remote::accept(&listener).unwrap();
+        let (_, result) = play_against(Box::new(Resigner), remote);
+
+        let received = client.join().unwrap().unwrap();
+        assert_eq!(received, result);
+        assert_eq!(received.outcome, Outcome::Resignation(TurnPlayer::Dark));
+        assert_eq!(received.winner, Some(TurnPlayer::Light));
+    }
+
+    #[test]
+    fn test_time_left_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut client = Client::join(addr).unwrap();
+            let bot = Bot::default();
+            client.run(&bot, &bot).unwrap();
+            bot.time_left.get()
+        });
+
+        let remote = Remote::accept(&listener).unwrap();
+        let mut ruversi = Ruversi::new(
+            Board::initial(),
+            Box::new(Computer::new(1)),
+            Box::new(remote),
+            Box::new(Bot::default()),
+        );
+        ruversi.set_time_control(TimeControl::parse("300/30x3").unwrap());
+        ruversi.run();
+
+        let time_left = client.join().unwrap().unwrap();
+        assert!(time_left.main <= Duration::from_secs(300));
+        assert_eq!(time_left.period, Duration::from_secs(30));
+        assert_eq!(time_left.periods, 3);
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // The peer leaves as soon as it is welcomed at the start.
+        let client = thread::spawn(move || drop(Client::join(addr).unwrap()));
+        let remote = Remote::accept(&listener).unwrap();
+
+        let board = play(remote);
+        client.join().unwrap();
+        assert!(!board.exists_legal_mov(Disk::Dark));
+        assert!(!board.exists_legal_mov(Disk::Light));
+    }
+}
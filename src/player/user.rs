@@ -1,24 +1,21 @@
 use crate::core::{
-    board::{Board, Disk, Position},
-    player::Player,
+    player::{Action, MoveContext, Player},
     ruversi::Input,
 };
 
+/// A player at the terminal or in the browser, whose actions are read from
+/// an `Input`.
 pub struct User {
     input: Box<dyn Input>,
 }
 
 impl Player for User {
-    fn init(&mut self, _board: Board) {
-        // nothing to do
+    fn mov(&mut self, _ctx: &MoveContext) -> Action {
+        self.input.input_action()
     }
 
-    fn update(&mut self, _pos: Position, _disk: Disk) {
-        // nothing to do
-    }
-
-    fn mov(&self) -> Position {
-        self.input.input_pos()
+    fn accept_draw(&mut self, _ctx: &MoveContext) -> bool {
+        self.input.input_accept_draw()
     }
 }
 
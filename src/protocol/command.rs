@@ -0,0 +1,257 @@
+use std::fmt;
+
+use crate::core::board::{Board, Position};
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::TurnPlayer;
+
+/// The commands `Engine` understands, in the order of `list_commands`.
+pub const COMMAND_NAMES: [&str; 17] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "setboard",
+    "play",
+    "genmove",
+    "undo",
+    "showboard",
+    "final_score",
+    "time_settings",
+    "time_left",
+    "rules",
+];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Vertex {
+    Move(Position),
+    Pass,
+}
+
+impl fmt::Display for Vertex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Move(pos) => write!(f, "{}", pos.to_notation()),
+            Self::Pass => write!(f, "pass"),
+        }
+    }
+}
+
+impl Vertex {
+    pub fn try_from_str(source: &str) -> Result<Self, String> {
+        if source.eq_ignore_ascii_case("pass") {
+            return Ok(Self::Pass);
+        }
+        Position::try_from_notation(source)
+            .map(Self::Move)
+            .map_err(|_| format!("invalid vertex: {}", source))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Command {
+    ProtocolVersion,
+    Name,
+    Version,
+    KnownCommand(String),
+    ListCommands,
+    Quit,
+    BoardSize(usize),
+    ClearBoard,
+    /// Sets up an arbitrary position given as a character of `x`, `o` or `_`
+    /// for every square in the form of `Board::try_from_str`, e.g. 64 for
+    /// the standard board. This is an extension of GTP.
+    SetBoard(Board),
+    Play(TurnPlayer, Vertex),
+    GenMove(TurnPlayer),
+    Undo,
+    ShowBoard,
+    FinalScore,
+    /// Main time and byo-yomi time are in seconds.
+    TimeSettings {
+        main_time: u64,
+        byo_yomi_time: u64,
+        byo_yomi_stones: u64,
+    },
+    TimeLeft(TurnPlayer, u64, u64),
+    /// Sets the rules deciding the winner, `standard` or `anti-reversi`,
+    /// which are kept over `clear_board`. This is an extension of GTP.
+    Rules(RuleSet),
+}
+
+fn color_name(turn: &TurnPlayer) -> &'static str {
+    match turn {
+        TurnPlayer::Dark => "black",
+        TurnPlayer::Light => "white",
+    }
+}
+
+fn parse_color(source: &str) -> Result<TurnPlayer, String> {
+    match source.to_ascii_lowercase().as_str() {
+        "b" | "black" | "x" | "dark" => Ok(TurnPlayer::Dark),
+        "w" | "white" | "o" | "light" => Ok(TurnPlayer::Light),
+        _ => Err(format!("invalid color: {}", source)),
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(source: &str) -> Result<T, String> {
+    source
+        .parse()
+        .map_err(|_| format!("invalid number: {}", source))
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProtocolVersion => write!(f, "protocol_version"),
+            Self::Name => write!(f, "name"),
+            Self::Version => write!(f, "version"),
+            Self::KnownCommand(name) => write!(f, "known_command {}", name),
+            Self::ListCommands => write!(f, "list_commands"),
+            Self::Quit => write!(f, "quit"),
+            Self::BoardSize(size) => write!(f, "boardsize {}", size),
+            Self::ClearBoard => write!(f, "clear_board"),
+            Self::SetBoard(board) => write!(f, "setboard {}", board.to_compact_str()),
+            Self::Play(turn, vertex) => write!(f, "play {} {}", color_name(turn), vertex),
+            Self::GenMove(turn) => write!(f, "genmove {}", color_name(turn)),
+            Self::Undo => write!(f, "undo"),
+            Self::ShowBoard => write!(f, "showboard"),
+            Self::FinalScore => write!(f, "final_score"),
+            Self::TimeSettings {
+                main_time,
+                byo_yomi_time,
+                byo_yomi_stones,
+            } => write!(
+                f,
+                "time_settings {} {} {}",
+                main_time, byo_yomi_time, byo_yomi_stones
+            ),
+            Self::TimeLeft(turn, time, stones) => {
+                write!(f, "time_left {} {} {}", color_name(turn), time, stones)
+            }
+            Self::Rules(rules) => write!(f, "rules {}", rules),
+        }
+    }
+}
+
+impl Command {
+    /// Parses a command line without its optional id.
+    pub fn try_from_str(source: &str) -> Result<Self, String> {
+        let mut args = source.split_whitespace();
+        let name = args.next().ok_or_else(|| "empty command".to_string())?;
+        let mut arg = || args.next().ok_or_else(|| "missing argument".to_string());
+
+        let command = match name {
+            "protocol_version" => Self::ProtocolVersion,
+            "name" => Self::Name,
+            "version" => Self::Version,
+            "known_command" => Self::KnownCommand(arg()?.to_string()),
+            "list_commands" => Self::ListCommands,
+            "quit" => Self::Quit,
+            "boardsize" => Self::BoardSize(parse_num(arg()?)?),
+            "clear_board" => Self::ClearBoard,
+            "setboard" => Self::SetBoard(
+                Board::try_from_str(arg()?).map_err(|msg| format!("invalid board: {}", msg))?,
+            ),
+            "play" => {
+                let turn = parse_color(arg()?)?;
+                Self::Play(turn, Vertex::try_from_str(arg()?)?)
+            }
+            "genmove" => Self::GenMove(parse_color(arg()?)?),
+            "undo" => Self::Undo,
+            "showboard" => Self::ShowBoard,
+            "final_score" => Self::FinalScore,
+            "time_settings" => Self::TimeSettings {
+                main_time: parse_num(arg()?)?,
+                byo_yomi_time: parse_num(arg()?)?,
+                byo_yomi_stones: parse_num(arg()?)?,
+            },
+            "time_left" => {
+                let turn = parse_color(arg()?)?;
+                Self::TimeLeft(turn, parse_num(arg()?)?, parse_num(arg()?)?)
+            }
+            "rules" => Self::Rules(RuleSet::parse(arg()?)?),
+            _ => return Err("unknown command".to_string()),
+        };
+
+        Ok(command)
+    }
+}
+
+/// Splits a request line into its optional numeric id and the command text.
+/// Returns `None` for blank lines and comments, which get no response.
+pub fn split_id(line: &str) -> Option<(Option<u32>, &str)> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    match line.split_once(char::is_whitespace) {
+        Some((id, rest)) if id.parse::<u32>().is_ok() => Some((id.parse().ok(), rest.trim())),
+        None if line.parse::<u32>().is_ok() => Some((line.parse().ok(), "")),
+        _ => Some((None, line)),
+    }
+}
+
+/// Formats a response as `=[id] text` or `?[id] text` followed by a blank
+/// line.
+pub fn format_response(id: Option<u32>, response: &Result<String, String>) -> String {
+    let (mark, text) = match response {
+        Ok(text) => ('=', text),
+        Err(text) => ('?', text),
+    };
+    let id = id.map(|id| id.to_string()).unwrap_or_default();
+
+    if text.is_empty() {
+        format!("{}{}\n\n", mark, id)
+    } else {
+        format!("{}{} {}\n\n", mark, id, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let lines = [
+            "play black d3",
+            "play white pass",
+            "genmove white",
+            "time_settings 300 10 1",
+            "boardsize 8",
+            "rules anti-reversi",
+        ];
+        for line in lines {
+            let command = Command::try_from_str(line).unwrap();
+            assert_eq!(command.to_string(), line);
+        }
+
+        assert_eq!(
+            Command::try_from_str("play B D3"),
+            Ok(Command::Play(
+                TurnPlayer::Dark,
+                Vertex::Move(Position::new(3, 2))
+            ))
+        );
+        assert!(Command::try_from_str("play black").is_err());
+        assert!(Command::try_from_str("play red d3").is_err());
+        assert!(Command::try_from_str("fly").is_err());
+    }
+
+    #[test]
+    fn test_split_id() {
+        assert_eq!(split_id("12 genmove b"), Some((Some(12), "genmove b")));
+        assert_eq!(split_id("genmove b # comment"), Some((None, "genmove b")));
+        assert_eq!(split_id("  # comment"), None);
+        assert_eq!(format_response(Some(3), &Ok("d3".to_string())), "=3 d3\n\n");
+        assert_eq!(
+            format_response(None, &Err("illegal move".to_string())),
+            "? illegal move\n\n"
+        );
+    }
+}
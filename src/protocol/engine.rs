@@ -0,0 +1,374 @@
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::core::board::{Board, Disk};
+use crate::core::clock::TimeLeft;
+use crate::core::player::{Action, MoveContext, Player};
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::TurnPlayer;
+
+use super::command::{self, Command, Vertex, COMMAND_NAMES};
+
+pub const PROTOCOL_VERSION: &str = "2";
+pub const ENGINE_NAME: &str = "ruversi";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TimeSettings {
+    pub main_time: u64,
+    pub byo_yomi_time: u64,
+    pub byo_yomi_stones: u64,
+}
+
+impl TimeSettings {
+    /// The time for a move in byo-yomi, if there is byo-yomi.
+    fn period(&self) -> Option<Duration> {
+        (self.byo_yomi_stones > 0)
+            .then(|| Duration::from_secs(self.byo_yomi_time / self.byo_yomi_stones))
+    }
+}
+
+/// Serves the text engine protocol for a board and a pair of players.
+///
+/// A new pair of players is made with `new_player` whenever the game is
+/// cleared. Both players are told every move and pass, start a new game
+/// from the position after `setboard` or under the rules after `rules`, and
+/// are told of each `undo`. The time given by the last `time_left` of a
+/// side is passed to its player on `genmove`.
+pub struct Engine {
+    board: Board,
+    history: Vec<Board>,
+    new_player: Box<dyn Fn() -> Box<dyn Player>>,
+    player_dark: Box<dyn Player>,
+    player_light: Box<dyn Player>,
+    time_settings: Option<TimeSettings>,
+    /// The time left of dark and light.
+    time_left: (Option<TimeLeft>, Option<TimeLeft>),
+    rules: RuleSet,
+}
+
+impl Engine {
+    pub fn new(new_player: Box<dyn Fn() -> Box<dyn Player>>) -> Self {
+        let board = Board::initial();
+        let mut player_dark = new_player();
+        let mut player_light = new_player();
+        player_dark.new_game(&board, TurnPlayer::Dark, RuleSet::Standard);
+        player_light.new_game(&board, TurnPlayer::Light, RuleSet::Standard);
+
+        Self {
+            board,
+            history: Vec::new(),
+            new_player,
+            player_dark,
+            player_light,
+            time_settings: None,
+            time_left: (None, None),
+            rules: RuleSet::Standard,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn time_settings(&self) -> Option<TimeSettings> {
+        self.time_settings
+    }
+
+    pub fn time_left(&self, turn: TurnPlayer) -> Option<TimeLeft> {
+        match turn {
+            TurnPlayer::Dark => self.time_left.0,
+            TurnPlayer::Light => self.time_left.1,
+        }
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Reads commands from `input` and writes responses to `output` until
+    /// `quit` or the end of the input.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let (id, text) = match command::split_id(&line) {
+                Some(request) => request,
+                None => continue,
+            };
+
+            let parsed = Command::try_from_str(text);
+            let response = parsed.clone().and_then(|command| self.execute(&command));
+            output.write_all(command::format_response(id, &response).as_bytes())?;
+            output.flush()?;
+
+            if parsed == Ok(Command::Quit) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn execute(&mut self, command: &Command) -> Result<String, String> {
+        match command {
+            Command::ProtocolVersion => Ok(PROTOCOL_VERSION.to_string()),
+            Command::Name => Ok(ENGINE_NAME.to_string()),
+            Command::Version => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            Command::KnownCommand(name) => Ok(COMMAND_NAMES.contains(&name.as_str()).to_string()),
+            Command::ListCommands => Ok(COMMAND_NAMES.join("\n")),
+            Command::Quit => Ok(String::new()),
+            Command::BoardSize(size) => match Board::with_size(*size) {
+                Ok(_) => {
+                    self.clear_board(*size);
+                    Ok(String::new())
+                }
+                Err(_) => Err("unacceptable size".to_string()),
+            },
+            Command::ClearBoard => {
+                self.clear_board(self.board.size());
+                Ok(String::new())
+            }
+            Command::SetBoard(board) => {
+                self.history.push(self.board.clone());
+                self.board = board.clone();
+                self.init_players();
+                Ok(String::new())
+            }
+            Command::Play(turn, vertex) => self.play(*turn, vertex).map(|_| String::new()),
+            Command::GenMove(turn) => self.genmove(*turn).map(|vertex| vertex.to_string()),
+            Command::Undo => match self.history.pop() {
+                Some(board) => {
+                    self.board = board;
+                    self.player_dark.on_undo(&self.board);
+                    self.player_light.on_undo(&self.board);
+                    Ok(String::new())
+                }
+                None => Err("cannot undo".to_string()),
+            },
+            Command::ShowBoard => Ok(format!("\n{}", self.board)),
+            Command::FinalScore => Ok(self.final_score()),
+            Command::TimeSettings {
+                main_time,
+                byo_yomi_time,
+                byo_yomi_stones,
+            } => {
+                self.time_settings = Some(TimeSettings {
+                    main_time: *main_time,
+                    byo_yomi_time: *byo_yomi_time,
+                    byo_yomi_stones: *byo_yomi_stones,
+                });
+                self.time_left = (None, None);
+                Ok(String::new())
+            }
+            Command::TimeLeft(turn, time, stones) => {
+                let time = Duration::from_secs(*time);
+                // A period of Canadian byo-yomi is shared out to its moves.
+                let time_left = match *stones {
+                    0 => {
+                        let period = self.time_settings.and_then(|settings| settings.period());
+                        TimeLeft {
+                            main: time,
+                            period: period.unwrap_or_default(),
+                            periods: u32::from(period.is_some()),
+                        }
+                    }
+                    stones => TimeLeft {
+                        main: Duration::ZERO,
+                        period: time / stones as u32,
+                        periods: 1,
+                    },
+                };
+                match turn {
+                    TurnPlayer::Dark => self.time_left.0 = Some(time_left),
+                    TurnPlayer::Light => self.time_left.1 = Some(time_left),
+                }
+                Ok(String::new())
+            }
+            Command::Rules(rules) => {
+                self.rules = *rules;
+                self.init_players();
+                Ok(String::new())
+            }
+        }
+    }
+
+    /// Starts over from the starting position of a board of `size`, which
+    /// has to be a valid size.
+    fn clear_board(&mut self, size: usize) {
+        self.board = Board::initial_with_size(size).expect("The size must be checked.");
+        self.history.clear();
+        self.player_dark = (self.new_player)();
+        self.player_light = (self.new_player)();
+        self.init_players();
+    }
+
+    fn init_players(&mut self) {
+        self.player_dark
+            .new_game(&self.board, TurnPlayer::Dark, self.rules);
+        self.player_light
+            .new_game(&self.board, TurnPlayer::Light, self.rules);
+    }
+
+    fn play(&mut self, turn: TurnPlayer, vertex: &Vertex) -> Result<(), String> {
+        match vertex {
+            Vertex::Move(pos) => {
+                let mut board = self.board.clone();
+                board
+                    .place(pos.clone(), turn.into_disk())
+                    .map_err(|_| "illegal move".to_string())?;
+
+                self.history.push(std::mem::replace(&mut self.board, board));
+                self.player_dark.on_move(pos, turn);
+                self.player_light.on_move(pos, turn);
+            }
+            Vertex::Pass => {
+                if self.board.exists_legal_mov(turn.into_disk()) {
+                    return Err("illegal move".to_string());
+                }
+                self.history.push(self.board.clone());
+                self.player_dark.on_pass(turn);
+                self.player_light.on_pass(turn);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn genmove(&mut self, turn: TurnPlayer) -> Result<Vertex, String> {
+        if !self.board.exists_legal_mov(turn.into_disk()) {
+            self.play(turn, &Vertex::Pass)?;
+            return Ok(Vertex::Pass);
+        }
+
+        let ctx = MoveContext {
+            board: &self.board,
+            side: turn,
+            time_left: self.time_left(turn),
+            rules: self.rules,
+        };
+        let action = match turn {
+            TurnPlayer::Dark => self.player_dark.mov(&ctx),
+            TurnPlayer::Light => self.player_light.mov(&ctx),
+        };
+        let Action::Move(pos) = action else {
+            return Err("the engine did not generate a move".to_string());
+        };
+        let vertex = Vertex::Move(pos);
+        self.play(turn, &vertex)
+            .map_err(|_| format!("the engine generated an illegal move {}", vertex))?;
+
+        Ok(vertex)
+    }
+
+    /// The winner under the rules and the difference of the disks.
+    fn final_score(&self) -> String {
+        let dark = self.board.count_disks(&Disk::Dark);
+        let light = self.board.count_disks(&Disk::Light);
+
+        match self.rules.winner(light, dark) {
+            Some(TurnPlayer::Dark) => format!("B+{}", dark.abs_diff(light)),
+            Some(TurnPlayer::Light) => format!("W+{}", dark.abs_diff(light)),
+            None => "0".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::Position;
+    use crate::player::computer::Computer;
+
+    fn engine() -> Engine {
+        Engine::new(Box::new(|| Box::new(Computer::new(1))))
+    }
+
+    fn session(engine: &mut Engine, input: &str) -> String {
+        let mut output = Vec::new();
+        engine.run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_session() {
+        let mut engine = engine();
+        let output = session(
+            &mut engine,
+            "1 name\n2 play black d3\n3 play white d3\n# comment\n4 final_score\n5 undo\n6 undo\n7 quit\n8 name\n",
+        );
+
+        assert_eq!(
+            output,
+            "=1 ruversi\n\n=2\n\n?3 illegal move\n\n=4 B+3\n\n=5\n\n?6 cannot undo\n\n=7\n\n"
+        );
+        assert_eq!(engine.board(), &Board::initial());
+    }
+
+    #[test]
+    fn test_boardsize() {
+        let mut engine = engine();
+        let output = session(
+            &mut engine,
+            "1 boardsize 7\n2 boardsize 6\n3 genmove black\n",
+        );
+
+        assert!(output.starts_with("?1 unacceptable size\n\n=2\n\n=3 "));
+        assert_eq!(engine.board().size(), 6);
+        assert_eq!(engine.board().count_disks(&Disk::Dark), 4);
+    }
+
+    #[test]
+    fn test_rules() {
+        let mut engine = engine();
+        let output = session(
+            &mut engine,
+            "1 rules anti\n2 play black d3\n3 final_score\n4 clear_board\n5 rules chess\n",
+        );
+
+        assert_eq!(
+            output,
+            "=1\n\n=2\n\n=3 W+3\n\n=4\n\n?5 unknown rules: chess\n\n"
+        );
+        assert_eq!(engine.rules(), RuleSet::AntiReversi);
+    }
+
+    #[test]
+    fn test_time() {
+        let mut engine = engine();
+        session(
+            &mut engine,
+            "1 time_settings 300 60 2\n2 time_left black 120 0\n3 time_left white 50 5\n",
+        );
+
+        let secs = Duration::from_secs;
+        assert_eq!(
+            engine.time_left(TurnPlayer::Dark),
+            Some(TimeLeft {
+                main: secs(120),
+                period: secs(30),
+                periods: 1
+            })
+        );
+        assert_eq!(
+            engine.time_left(TurnPlayer::Light),
+            Some(TimeLeft {
+                main: secs(0),
+                period: secs(10),
+                periods: 1
+            })
+        );
+
+        session(&mut engine, "4 time_settings 300 0 0\n");
+        assert_eq!(engine.time_left(TurnPlayer::Dark), None);
+    }
+
+    #[test]
+    fn test_genmove() {
+        let mut engine = engine();
+        let response = engine.execute(&Command::GenMove(TurnPlayer::Dark)).unwrap();
+        let pos = Position::try_from_notation(&response).unwrap();
+
+        let mut board = Board::initial();
+        assert!(board.place(pos, Disk::Dark).is_ok());
+        assert_eq!(engine.board(), &board);
+    }
+}
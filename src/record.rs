@@ -0,0 +1,152 @@
+pub mod ggf;
+pub mod json;
+pub mod wthor;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Board, Position};
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::TurnPlayer;
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub rating: Option<f64>,
+    /// The time control in the clock notation of GGF, e.g. `15:00//02:00`.
+    pub time_control: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ending {
+    Normal,
+    Resignation,
+    Timeout,
+    Agreement,
+    /// A player lost by making illegal moves.
+    Forfeit,
+    /// The game was stopped without a winner, with the disks counted then.
+    Aborted,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordResult {
+    /// The number of dark disks minus that of light disks.
+    pub score: f64,
+    pub ending: Ending,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedMove {
+    pub turn: TurnPlayer,
+    /// `None` for a pass.
+    pub pos: Option<Position>,
+    /// The evaluation by the player who made the move.
+    pub eval: Option<f64>,
+    /// The thinking time in seconds.
+    pub time: Option<f64>,
+}
+
+impl RecordedMove {
+    pub fn new(turn: TurnPlayer, pos: Option<Position>) -> Self {
+        Self {
+            turn,
+            pos,
+            eval: None,
+            time: None,
+        }
+    }
+}
+
+/// A whole game with the information about it.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameRecord {
+    pub dark: PlayerInfo,
+    pub light: PlayerInfo,
+    pub place: Option<String>,
+    pub date: Option<String>,
+    pub start: Board,
+    pub first_turn: TurnPlayer,
+    /// The rules deciding the winner. Left out when serialized if standard.
+    #[serde(default, skip_serializing_if = "RuleSet::is_standard")]
+    pub rules: RuleSet,
+    pub moves: Vec<RecordedMove>,
+    pub result: Option<RecordResult>,
+    /// Properties which are kept as they are, in the order of appearance.
+    pub extra: Vec<(String, String)>,
+}
+
+impl Default for GameRecord {
+    fn default() -> Self {
+        Self::new(Board::initial(), TurnPlayer::Dark)
+    }
+}
+
+impl GameRecord {
+    pub fn new(start: Board, first_turn: TurnPlayer) -> Self {
+        Self {
+            dark: PlayerInfo::default(),
+            light: PlayerInfo::default(),
+            place: None,
+            date: None,
+            start,
+            first_turn,
+            rules: RuleSet::Standard,
+            moves: Vec::new(),
+            result: None,
+            extra: Vec::new(),
+        }
+    }
+
+    /// Returns the boards before the first move and after every move,
+    /// checking each move with `Board::place`. A pass is accepted only when
+    /// the player has no legal move, and may also be left out of the moves.
+    pub fn boards(&self) -> Result<Vec<Board>, String> {
+        let mut turn = self.first_turn;
+        let mut board = self.start.clone();
+        let mut boards = vec![board.clone()];
+
+        for (i, mov) in self.moves.iter().enumerate() {
+            if mov.turn != turn {
+                if board.exists_legal_mov(turn.into_disk()) {
+                    return Err(format!("move {}: it is {}'s turn", i + 1, turn));
+                }
+                turn = mov.turn;
+            }
+
+            let disk = turn.into_disk();
+            match &mov.pos {
+                Some(pos) => {
+                    board.place(pos.clone(), disk).map_err(|msg| {
+                        format!("move {} ({} {}): {}", i + 1, turn, pos.to_notation(), msg)
+                    })?;
+                }
+                None if board.exists_legal_mov(disk) => {
+                    return Err(format!(
+                        "move {} ({} pass): a legal move exists",
+                        i + 1,
+                        turn
+                    ))
+                }
+                None => (),
+            }
+            boards.push(board.clone());
+            turn = TurnPlayer::other(&turn);
+        }
+
+        Ok(boards)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.boards().map(|_| ())
+    }
+
+    pub fn final_board(&self) -> Result<Board, String> {
+        self.boards()
+            .map(|boards| boards.last().cloned().expect("The start board must exist."))
+    }
+}
@@ -0,0 +1,447 @@
+use std::fmt::Write;
+
+use super::{Ending, GameRecord, RecordResult, RecordedMove};
+use crate::core::board::{Board, Disk, Position};
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::TurnPlayer;
+
+/// Parses the first game of a GGF source and validates its moves.
+pub fn parse(source: &str) -> Result<GameRecord, String> {
+    let mut games = parse_all(source);
+    if games.is_empty() {
+        return Err("no game is found".to_string());
+    }
+    games.swap_remove(0)
+}
+
+/// Parses every game of a GGF source, such as a file from an online server
+/// with one game per line.
+pub fn parse_all(source: &str) -> Vec<Result<GameRecord, String>> {
+    let mut games = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find("(;") {
+        rest = &rest[start + 2..];
+        let (props, next) = match read_properties(rest) {
+            Ok(parsed) => parsed,
+            Err(msg) => {
+                games.push(Err(msg));
+                break;
+            }
+        };
+        rest = next;
+        games.push(build(props).and_then(|record| record.validate().map(|_| record)));
+    }
+
+    games
+}
+
+type Properties = Vec<(String, String)>;
+
+/// Reads `KEY[value]` pairs up to the closing `;)`, and returns them with the
+/// rest of the source.
+fn read_properties(source: &str) -> Result<(Properties, &str), String> {
+    let mut props = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    loop {
+        let (i, c) = chars
+            .next()
+            .ok_or_else(|| "the game is not closed with ;)".to_string())?;
+        match c {
+            c if c.is_whitespace() => continue,
+            ';' if matches!(chars.peek(), Some((_, ')'))) => {
+                return Ok((props, &source[i + 2..]));
+            }
+            c if c.is_ascii_uppercase() => {
+                let mut key = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_uppercase()) {
+                    key.push(c);
+                }
+                if chars.next().map(|(_, c)| c) != Some('[') {
+                    return Err(format!("[ is expected after {}", key));
+                }
+
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => {
+                            if let Some((_, c)) = chars.next() {
+                                value.push(c);
+                            }
+                        }
+                        Some((_, ']')) => break,
+                        Some((_, c)) => value.push(c),
+                        None => return Err(format!("the value of {} is not closed", key)),
+                    }
+                }
+                props.push((key, value));
+            }
+            c => return Err(format!("unexpected character {:?}", c)),
+        }
+    }
+}
+
+fn build(props: Properties) -> Result<GameRecord, String> {
+    let mut record = GameRecord::default();
+    let mut time_control = None;
+    let mut game_type = None;
+
+    for (key, value) in props {
+        match key.as_str() {
+            "GM" if !value.eq_ignore_ascii_case("othello") => {
+                return Err(format!("not an Othello game: {}", value))
+            }
+            "GM" => (),
+            "PC" => record.place = Some(value),
+            "DT" => record.date = Some(value),
+            "PB" => record.dark.name = value,
+            "PW" => record.light.name = value,
+            "RB" => record.dark.rating = Some(parse_num(&key, &value)?),
+            "RW" => record.light.rating = Some(parse_num(&key, &value)?),
+            "TI" => time_control = Some(value),
+            "TY" => {
+                let (kind, rules) = parse_type(&value);
+                record.rules = rules;
+                game_type = Some(kind.to_string());
+            }
+            "TB" => record.dark.time_control = Some(value),
+            "TW" => record.light.time_control = Some(value),
+            "RE" => record.result = Some(parse_result(&value)?),
+            "BO" => {
+                let (board, turn) = parse_board(&value)?;
+                record.start = board;
+                record.first_turn = turn;
+            }
+            "B" => record.moves.push(parse_move(TurnPlayer::Dark, &value)?),
+            "W" => record.moves.push(parse_move(TurnPlayer::Light, &value)?),
+            _ => record.extra.push((key, value)),
+        }
+    }
+
+    if let Some(time_control) = time_control {
+        record.dark.time_control.get_or_insert(time_control.clone());
+        record.light.time_control.get_or_insert(time_control);
+    }
+    // Only a type other than the plain board size, such as a synchro game,
+    // is kept to be written back.
+    if let Some(kind) = game_type.filter(|kind| *kind != record.start.size().to_string()) {
+        record.extra.push(("TY".to_string(), kind));
+    }
+
+    Ok(record)
+}
+
+fn parse_num(key: &str, value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{}[{}] is not a number", key, value))
+}
+
+/// Splits the `a` suffix of anti-reversi off a game type such as `8a`.
+fn parse_type(value: &str) -> (&str, RuleSet) {
+    match value.strip_suffix('a') {
+        Some(kind) => (kind, RuleSet::AntiReversi),
+        None => (value, RuleSet::Standard),
+    }
+}
+
+fn parse_result(value: &str) -> Result<RecordResult, String> {
+    let (score, ending) = match value.split_once(':') {
+        Some((score, "r")) => (score, Ending::Resignation),
+        Some((score, "t")) => (score, Ending::Timeout),
+        Some((score, "s")) => (score, Ending::Agreement),
+        Some((_, ending)) => return Err(format!("unknown ending in RE[{}]: {}", value, ending)),
+        None => (value, Ending::Normal),
+    };
+
+    Ok(RecordResult {
+        score: parse_num("RE", score)?,
+        ending,
+    })
+}
+
+fn parse_board(value: &str) -> Result<(Board, TurnPlayer), String> {
+    let mut words = value.split_whitespace();
+    let mut board = match words.next().map(|size| (size, size.parse())) {
+        Some((size, Ok(n))) => {
+            Board::with_size(n).map_err(|_| format!("unsupported board size: {}", size))?
+        }
+        Some((size, Err(_))) => return Err(format!("unsupported board size: {}", size)),
+        None => return Err("BO is empty".to_string()),
+    };
+
+    let count = board.size() * board.size();
+    let squares: Vec<char> = words.flat_map(|word| word.chars()).collect();
+    if squares.len() != count + 1 {
+        return Err(format!(
+            "BO must have {} squares and a side to move, but has {} characters",
+            count,
+            squares.len()
+        ));
+    }
+
+    for (pos, c) in board.positions().zip(&squares[..count]) {
+        match c {
+            '*' => board.set(&pos, Disk::Dark),
+            'O' => board.set(&pos, Disk::Light),
+            '-' => (),
+            c => return Err(format!("unknown square {:?} in BO", c)),
+        }
+    }
+
+    let turn = match squares[count] {
+        '*' => TurnPlayer::Dark,
+        'O' => TurnPlayer::Light,
+        c => return Err(format!("unknown side to move {:?} in BO", c)),
+    };
+
+    Ok((board, turn))
+}
+
+/// Parses a time in seconds, `m:s` or `h:m:s`.
+fn parse_time(value: &str) -> Result<f64, String> {
+    value.split(':').try_fold(0.0, |acc, part| {
+        part.parse::<f64>()
+            .map(|n| acc * 60.0 + n)
+            .map_err(|_| format!("invalid time: {}", value))
+    })
+}
+
+fn parse_move(turn: TurnPlayer, value: &str) -> Result<RecordedMove, String> {
+    let mut parts = value.split('/');
+    let pos = match parts.next().map(str::trim) {
+        Some(pass) if pass.eq_ignore_ascii_case("pa") || pass.eq_ignore_ascii_case("pass") => None,
+        Some(pos) => {
+            Some(Position::try_from_notation(pos).map_err(|msg| format!("{}: {}", msg, value))?)
+        }
+        None => return Err("a move is empty".to_string()),
+    };
+
+    let mut mov = RecordedMove::new(turn, pos);
+    match parts.next().map(str::trim) {
+        Some("") | None => (),
+        Some(eval) => mov.eval = Some(parse_num("eval", eval)?),
+    }
+    match parts.next().map(str::trim) {
+        Some("") | None => (),
+        Some(time) => mov.time = Some(parse_time(time)?),
+    }
+
+    Ok(mov)
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+fn format_board(board: &Board, turn: TurnPlayer) -> String {
+    let mut s = board.size().to_string();
+    for (i, pos) in board.positions().enumerate() {
+        if i % board.size() == 0 {
+            s.push(' ');
+        }
+        s.push(match board.get(&pos) {
+            Some(Disk::Dark) => '*',
+            Some(Disk::Light) => 'O',
+            None => '-',
+        });
+    }
+    s.push(' ');
+    s.push(match turn {
+        TurnPlayer::Dark => '*',
+        TurnPlayer::Light => 'O',
+    });
+    s
+}
+
+fn format_move(mov: &RecordedMove) -> String {
+    let mut s = match &mov.pos {
+        Some(pos) => pos.to_notation(),
+        None => "pa".to_string(),
+    };
+    if mov.eval.is_some() || mov.time.is_some() {
+        s.push('/');
+        if let Some(eval) = mov.eval {
+            let _ = write!(s, "{:.2}", eval);
+        }
+        if let Some(time) = mov.time {
+            let _ = write!(s, "/{:.2}", time);
+        }
+    }
+    s
+}
+
+/// Writes a record as a single line GGF game.
+pub fn to_string(record: &GameRecord) -> String {
+    let mut s = String::from("(;GM[Othello]");
+    let mut prop = |key: &str, value: &str| {
+        let _ = write!(s, "{}[{}]", key, escape(value));
+    };
+
+    if let Some(place) = &record.place {
+        prop("PC", place);
+    }
+    if let Some(date) = &record.date {
+        prop("DT", date);
+    }
+    prop("PB", &record.dark.name);
+    prop("PW", &record.light.name);
+    if let Some(rating) = record.dark.rating {
+        prop("RB", &rating.to_string());
+    }
+    if let Some(rating) = record.light.rating {
+        prop("RW", &rating.to_string());
+    }
+    match (&record.dark.time_control, &record.light.time_control) {
+        (Some(dark), Some(light)) if dark == light => prop("TI", dark),
+        (dark, light) => {
+            if let Some(dark) = dark {
+                prop("TB", dark);
+            }
+            if let Some(light) = light {
+                prop("TW", light);
+            }
+        }
+    }
+    let mut kind = match record.extra.iter().find(|(key, _)| key == "TY") {
+        Some((_, kind)) => kind.clone(),
+        None => record.start.size().to_string(),
+    };
+    if record.rules == RuleSet::AntiReversi {
+        kind.push('a');
+    }
+    prop("TY", &kind);
+    for (key, value) in record.extra.iter().filter(|(key, _)| key != "TY") {
+        prop(key, value);
+    }
+    // GGF has no forfeit, which is written as a resignation, nor a result
+    // for an aborted game.
+    if let Some(result) = record
+        .result
+        .filter(|result| result.ending != Ending::Aborted)
+    {
+        let ending = match result.ending {
+            Ending::Normal | Ending::Aborted => "",
+            Ending::Resignation | Ending::Forfeit => ":r",
+            Ending::Timeout => ":t",
+            Ending::Agreement => ":s",
+        };
+        prop("RE", &format!("{:+.2}{}", result.score, ending));
+    }
+    prop("BO", &format_board(&record.start, record.first_turn));
+    for mov in &record.moves {
+        let key = match mov.turn {
+            TurnPlayer::Dark => "B",
+            TurnPlayer::Light => "W",
+        };
+        prop(key, &format_move(mov));
+    }
+
+    s.push_str(";)");
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME: &str = "(;GM[Othello]PC[NOS]DT[2003.12.15_13:24:03.MET]PB[Saio1200]PW[Saio3000]\
+        RB[2197.72]RW[2413.05]TI[15:00//02:00]TY[8]RE[-64.00:r]\
+        BO[8 -------- -------- -------- ---O*--- ---*O--- -------- -------- -------- *]\
+        B[d3//0.01]W[c5/-1.00/0.02]B[F6]W[f5//1:02];)";
+
+    #[test]
+    fn test_parse() {
+        let record = parse(GAME).unwrap();
+
+        assert_eq!(record.place.as_deref(), Some("NOS"));
+        assert_eq!(record.dark.name, "Saio1200");
+        assert_eq!(record.light.rating, Some(2413.05));
+        assert_eq!(record.dark.time_control.as_deref(), Some("15:00//02:00"));
+        assert_eq!(
+            record.result,
+            Some(RecordResult {
+                score: -64.0,
+                ending: Ending::Resignation
+            })
+        );
+        assert_eq!(record.start, Board::initial());
+        assert_eq!(record.first_turn, TurnPlayer::Dark);
+        assert_eq!(record.moves.len(), 4);
+        assert_eq!(record.moves[1].pos, Some(Position::new(2, 4)));
+        assert_eq!(record.moves[1].eval, Some(-1.0));
+        assert_eq!(record.moves[3].time, Some(62.0));
+        assert_eq!(record.final_board().unwrap().count_disks(&Disk::Light), 4);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let record = parse(GAME).unwrap();
+        let written = to_string(&record);
+        assert_eq!(parse(&written), Ok(record));
+    }
+
+    #[test]
+    fn test_other_size() {
+        let source = "(;GM[Othello]PB[a]PW[b]TY[6]\
+            BO[6 ------ ------ --O*-- --*O-- ------ ------ *]B[c2];)";
+        let record = parse(source).unwrap();
+        assert_eq!(record.start, Board::initial_with_size(6).unwrap());
+        assert_eq!(record.final_board().unwrap().count_disks(&Disk::Dark), 4);
+        assert_eq!(parse(&to_string(&record)), Ok(record.clone()));
+
+        let mut record = record;
+        record.extra.clear();
+        assert!(to_string(&record).contains("TY[6]BO[6 ------ "));
+    }
+
+    #[test]
+    fn test_anti_reversi() {
+        let source = GAME.replace("TY[8]", "TY[8a]");
+        let record = parse(&source).unwrap();
+        assert_eq!(record.rules, RuleSet::AntiReversi);
+        assert!(record.extra.is_empty());
+
+        let written = to_string(&record);
+        assert!(written.contains("TY[8a]"));
+        assert_eq!(parse(&written), Ok(record.clone()));
+
+        let mut record = record;
+        record.rules = RuleSet::Standard;
+        assert!(to_string(&record).contains("TY[8]"));
+
+        let synchro = parse(&GAME.replace("TY[8]", "TY[s8a]")).unwrap();
+        assert_eq!(synchro.rules, RuleSet::AntiReversi);
+        assert!(to_string(&synchro).contains("TY[s8a]"));
+    }
+
+    #[test]
+    fn test_write_endings() {
+        let mut record = parse(GAME).unwrap();
+        record.result.as_mut().unwrap().ending = Ending::Forfeit;
+        assert!(to_string(&record).contains("RE[-64.00:r]"));
+        record.result.as_mut().unwrap().ending = Ending::Aborted;
+        assert!(!to_string(&record).contains("RE["));
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let source = format!("{}\n{}\n", GAME, GAME.replace("W[c5", "W[a1"));
+        let games = parse_all(&source);
+
+        assert_eq!(games.len(), 2);
+        assert!(games[0].is_ok());
+        assert!(games[1].as_ref().unwrap_err().starts_with("move 2"));
+    }
+
+    #[test]
+    fn test_parse_err() {
+        assert!(parse("(;GM[Chess];)").is_err());
+        assert!(parse("(;GM[Othello]BO[10 *];)").is_err());
+        assert!(parse("(;GM[Othello]BO[7 *];)").is_err());
+        assert!(parse("(;GM[Othello]PB[x").is_err());
+        assert!(parse("nothing").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Game records as JSON documents for other tools.
+//!
+//! A document is an object with the schema version and the record:
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "record": {
+//!     "dark": {"name": "Alice", "rating": 1800.0, "time_control": "15:00//02:00"},
+//!     "light": {"name": "Bob", "rating": null, "time_control": null},
+//!     "place": "NOS",
+//!     "date": "2024.01.15",
+//!     "start": ["________", "________", "________", "___ox___",
+//!               "___xo___", "________", "________", "________"],
+//!     "first_turn": "dark",
+//!     "rules": "anti_reversi",
+//!     "moves": [
+//!       {"turn": "dark", "pos": "d3", "eval": 0.5, "time": 1.2},
+//!       {"turn": "light", "pos": null, "eval": null, "time": null}
+//!     ],
+//!     "result": {"score": -2.0, "ending": "normal"},
+//!     "extra": [["TY", "s8"]]
+//!   }
+//! }
+//! ```
+//!
+//! - A board is its rows from the top, 8 of 8 squares on the standard
+//!   board and N of N for any even N from 4 to 16: `x` for dark, `o` for
+//!   light and `_` for empty.
+//! - A position is a column `a` to `p` and a row `1` to `16`; `null` in a
+//!   move is a pass.
+//! - A disk or a turn is `"dark"` or `"light"`.
+//! - The rules are `"standard"`, the default when left out, or
+//!   `"anti_reversi"`, where the player with fewer disks wins.
+//! - A score is the number of dark disks minus that of light disks, and an
+//!   ending is `normal`, `resignation`, `timeout`, `agreement`, `forfeit`
+//!   or `aborted`.
+//! - A `GameResult` is `{"light_disks": 30, "dark_disks": 34, "winner":
+//!   "dark"}` with `null` for a draw. A game not ended normally has an
+//!   `outcome` such as `{"resignation": "light"}` or `"draw_agreed"`, and a
+//!   game not played under the standard rules has its `rules`.
+//!
+//! Optional fields may be `null` or left out. Unknown fields, malformed
+//! boards and positions, and records with illegal moves are rejected, but a
+//! board which can not be reached in a game is accepted.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::GameRecord;
+
+/// The version of the schema, raised on an incompatible change.
+pub const VERSION: u64 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document<R> {
+    version: u64,
+    record: R,
+}
+
+pub fn to_string(record: &GameRecord) -> String {
+    serde_json::to_string(&Document {
+        version: VERSION,
+        record,
+    })
+    .expect("A record must be serializable.")
+}
+
+pub fn to_string_pretty(record: &GameRecord) -> String {
+    serde_json::to_string_pretty(&Document {
+        version: VERSION,
+        record,
+    })
+    .expect("A record must be serializable.")
+}
+
+/// Parses a document and validates the moves of the record.
+pub fn parse(source: &str) -> Result<GameRecord, String> {
+    let value: Value = serde_json::from_str(source).map_err(|e| e.to_string())?;
+    match value.get("version").map(Value::as_u64) {
+        Some(Some(VERSION)) => (),
+        Some(Some(version)) => return Err(format!("unsupported version: {}", version)),
+        Some(None) => return Err("version must be a number".to_string()),
+        None => return Err("missing field `version`".to_string()),
+    }
+
+    let document: Document<GameRecord> =
+        serde_json::from_value(value).map_err(|e| e.to_string())?;
+    document.record.validate()?;
+    Ok(document.record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::{Board, Disk, Position};
+    use crate::core::rules::RuleSet;
+    use crate::core::ruversi::{GameResult, TurnPlayer};
+    use crate::record::ggf;
+
+    const GAME: &str = "(;GM[Othello]PC[NOS]PB[Saio1200]PW[Saio3000]RB[2197.72]\
+        TI[15:00//02:00]RE[-64.00:r]\
+        BO[8 -------- -------- -------- ---O*--- ---*O--- -------- -------- -------- *]\
+        B[d3//0.01]W[c5/-1.00/0.02]B[F6]W[f5];)";
+
+    #[test]
+    fn test_round_trip() {
+        let record = ggf::parse(GAME).unwrap();
+        assert_eq!(parse(&to_string(&record)), Ok(record.clone()));
+        assert_eq!(parse(&to_string_pretty(&record)), Ok(record.clone()));
+        assert!(!to_string(&record).contains("rules"));
+
+        let mut anti = record;
+        anti.rules = RuleSet::AntiReversi;
+        let json = to_string(&anti);
+        assert!(json.contains(r#""rules":"anti_reversi""#));
+        assert_eq!(parse(&json), Ok(anti));
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(serde_json::to_string(&Disk::Dark).unwrap(), r#""dark""#);
+        assert_eq!(
+            serde_json::to_string(&TurnPlayer::Light).unwrap(),
+            r#""light""#
+        );
+        assert_eq!(
+            serde_json::to_string(&Position::new(3, 2)).unwrap(),
+            r#""d3""#
+        );
+        assert_eq!(
+            serde_json::to_string(&GameResult::new(RuleSet::Standard, 30, 34)).unwrap(),
+            r#"{"light_disks":30,"dark_disks":34,"winner":"dark"}"#
+        );
+
+        let rows = serde_json::to_value(Board::initial()).unwrap();
+        assert_eq!(rows[3], "___ox___");
+        assert_eq!(
+            serde_json::from_value::<Board>(rows).unwrap(),
+            Board::initial()
+        );
+    }
+
+    #[test]
+    fn test_reject() {
+        let row = r#""________""#;
+        let rows = |n: usize| format!("[{}]", vec![row; n].join(","));
+        assert!(serde_json::from_str::<Board>(&rows(8)).is_ok());
+        assert!(serde_json::from_str::<Board>(&rows(7)).is_err());
+        assert!(serde_json::from_str::<Board>(&rows(9)).is_err());
+        let bad = rows(8).replacen("________", "_______", 1);
+        assert!(serde_json::from_str::<Board>(&bad).is_err());
+        let bad = rows(8).replacen("________", "___#____", 1);
+        assert!(serde_json::from_str::<Board>(&bad).is_err());
+
+        assert!(serde_json::from_str::<Position>(r#""q9""#).is_err());
+        assert!(serde_json::from_str::<Disk>(r#""red""#).is_err());
+        assert!(serde_json::from_str::<GameResult>(
+            r#"{"light_disks":30,"dark_disks":34,"winner":"light"}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<GameResult>(
+            r#"{"light_disks":150,"dark_disks":120,"winner":"light"}"#
+        )
+        .is_err());
+
+        let record = ggf::parse(GAME).unwrap();
+        let json = to_string(&record);
+        assert!(parse(&json.replace(r#""version":1"#, r#""version":2"#)).is_err());
+        assert!(parse(&json.replace(r#""version":1,"#, "")).is_err());
+        assert!(parse(&json.replace(r#""place""#, r#""venue""#)).is_err());
+        assert!(parse(&json.replace(r#""pos":"c5""#, r#""pos":"a1""#)).is_err());
+    }
+}
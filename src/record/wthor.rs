@@ -0,0 +1,293 @@
+use super::{Ending, GameRecord, RecordResult, RecordedMove};
+use crate::core::board::{Board, Position};
+use crate::core::ruversi::TurnPlayer;
+
+pub const HEADER_LEN: usize = 16;
+pub const GAME_LEN: usize = 68;
+pub const PLAYER_NAME_LEN: usize = 20;
+pub const TOURNAMENT_NAME_LEN: usize = 26;
+
+const MOVES_OFFSET: usize = 8;
+const MAX_MOVES: usize = 60;
+
+/// The 16 byte header shared by `.wtb`, `.JOU` and `.TRN` files.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Header {
+    /// The date the file was made as (year, month, day).
+    pub created: (u16, u8, u8),
+    /// The number of games in a `.wtb` file.
+    pub games: u32,
+    /// The number of names in a `.JOU` or `.TRN` file.
+    pub records: u16,
+    /// The year the games were played.
+    pub year: u16,
+    pub board_size: u8,
+    pub solitaire: bool,
+    /// The number of empty squares from which the theoretical scores are
+    /// computed.
+    pub depth: u8,
+}
+
+impl Header {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err(format!(
+                "the header needs {} bytes, but the file has {}",
+                HEADER_LEN,
+                bytes.len()
+            ));
+        }
+
+        Ok(Self {
+            created: (bytes[0] as u16 * 100 + bytes[1] as u16, bytes[2], bytes[3]),
+            games: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            records: u16::from_le_bytes([bytes[8], bytes[9]]),
+            year: u16::from_le_bytes([bytes[10], bytes[11]]),
+            board_size: bytes[12],
+            solitaire: bytes[13] == 1,
+            depth: bytes[14],
+        })
+    }
+}
+
+/// A game of a `.wtb` file. Players and the tournament are numbers into the
+/// `.JOU` and `.TRN` files.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WthorGame {
+    pub tournament: u16,
+    pub dark_player: u16,
+    pub light_player: u16,
+    /// The number of dark disks at the end.
+    pub score: u8,
+    /// The number of dark disks with perfect play from `Header::depth`
+    /// empty squares on.
+    pub theoretical_score: u8,
+    pub record: GameRecord,
+}
+
+impl WthorGame {
+    /// Fills the names of the players and the tournament in the record.
+    /// Numbers out of the lists are left unnamed.
+    pub fn with_names(mut self, players: &[String], tournaments: &[String]) -> Self {
+        let name = |names: &[String], i: u16| names.get(i as usize).cloned().unwrap_or_default();
+        self.record.dark.name = name(players, self.dark_player);
+        self.record.light.name = name(players, self.light_player);
+        self.record.place = tournaments.get(self.tournament as usize).cloned();
+        self
+    }
+}
+
+/// A `.wtb` game database.
+pub struct Wtb<'a> {
+    pub header: Header,
+    data: &'a [u8],
+}
+
+impl<'a> Wtb<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        let header = Header::parse(bytes)?;
+        if !matches!(header.board_size, 0 | 8) {
+            return Err(format!("unsupported board size: {}", header.board_size));
+        }
+
+        let data = &bytes[HEADER_LEN..];
+        let len = header.games as usize * GAME_LEN;
+        if data.len() < len {
+            return Err(format!(
+                "the header tells {} games, but the file has only {}",
+                header.games,
+                data.len() / GAME_LEN
+            ));
+        }
+
+        Ok(Self {
+            header,
+            data: &data[..len],
+        })
+    }
+
+    /// Decodes the games one by one. Each game is replayed with
+    /// `Board::place`, and a game with an illegal move is an error.
+    pub fn games(&self) -> impl Iterator<Item = Result<WthorGame, String>> + 'a {
+        let year = self.header.year;
+        self.data
+            .chunks_exact(GAME_LEN)
+            .enumerate()
+            .map(move |(i, bytes)| {
+                parse_game(bytes, year).map_err(|msg| format!("game {}: {}", i + 1, msg))
+            })
+    }
+}
+
+fn parse_game(bytes: &[u8], year: u16) -> Result<WthorGame, String> {
+    let mut board = Board::initial();
+    let mut turn = TurnPlayer::Dark;
+    let mut record = GameRecord::default();
+
+    for &code in &bytes[MOVES_OFFSET..MOVES_OFFSET + MAX_MOVES] {
+        if code == 0 {
+            break;
+        }
+
+        let pos = Position::try_new(code as i32 % 10 - 1, code as i32 / 10 - 1)
+            .map_err(|_| format!("invalid move code {}", code))?;
+
+        // Passes are not recorded; the other player moves instead.
+        if !board.exists_legal_mov(turn.into_disk()) {
+            turn = TurnPlayer::other(&turn);
+        }
+        board.place(pos.clone(), turn.into_disk()).map_err(|msg| {
+            format!(
+                "move {} ({} {}): {}",
+                record.moves.len() + 1,
+                turn,
+                pos.to_notation(),
+                msg
+            )
+        })?;
+
+        record.moves.push(RecordedMove::new(turn, Some(pos)));
+        turn = TurnPlayer::other(&turn);
+    }
+
+    let score = bytes[6];
+    record.date = Some(year.to_string());
+    record.result = Some(RecordResult {
+        score: 2.0 * score as f64 - 64.0,
+        ending: Ending::Normal,
+    });
+
+    Ok(WthorGame {
+        tournament: u16::from_le_bytes([bytes[0], bytes[1]]),
+        dark_player: u16::from_le_bytes([bytes[2], bytes[3]]),
+        light_player: u16::from_le_bytes([bytes[4], bytes[5]]),
+        score,
+        theoretical_score: bytes[7],
+        record,
+    })
+}
+
+/// Reads the names of a `.JOU` or `.TRN` file, each `width` bytes long,
+/// NUL padded and encoded in ISO-8859-1.
+pub fn parse_names(bytes: &[u8], width: usize) -> Result<Vec<String>, String> {
+    let header = Header::parse(bytes)?;
+    let data = &bytes[HEADER_LEN..];
+    let len = header.records as usize * width;
+    if data.len() < len {
+        return Err(format!(
+            "the header tells {} names, but the file has only {}",
+            header.records,
+            data.len() / width
+        ));
+    }
+
+    Ok(data[..len]
+        .chunks_exact(width)
+        .map(|name| {
+            name.iter()
+                .take_while(|&&b| b != 0)
+                .map(|&b| b as char)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect())
+}
+
+/// Reads a `.JOU` file.
+pub fn parse_players(bytes: &[u8]) -> Result<Vec<String>, String> {
+    parse_names(bytes, PLAYER_NAME_LEN)
+}
+
+/// Reads a `.TRN` file.
+pub fn parse_tournaments(bytes: &[u8]) -> Result<Vec<String>, String> {
+    parse_names(bytes, TOURNAMENT_NAME_LEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::Disk;
+
+    fn header(games: u32, records: u16) -> Vec<u8> {
+        let mut bytes = vec![20, 24, 1, 15];
+        bytes.extend(games.to_le_bytes());
+        bytes.extend(records.to_le_bytes());
+        bytes.extend(2023u16.to_le_bytes());
+        bytes.extend([8, 0, 22, 0]);
+        bytes
+    }
+
+    fn game(moves: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, 0, 0, 1, 0, 33, 32];
+        bytes.extend(moves);
+        bytes.resize(GAME_LEN, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_games() {
+        let mut bytes = header(2, 0);
+        // d3 c5 f6 f5
+        bytes.extend(game(&[34, 53, 66, 56]));
+        // d3 d3
+        bytes.extend(game(&[34, 34]));
+
+        let wtb = Wtb::parse(&bytes).unwrap();
+        assert_eq!(wtb.header.created, (2024, 1, 15));
+        assert_eq!(wtb.header.year, 2023);
+        assert_eq!(wtb.header.depth, 22);
+
+        let games: Vec<_> = wtb.games().collect();
+        let first = games[0].as_ref().unwrap();
+        assert_eq!(first.score, 33);
+        assert_eq!(first.theoretical_score, 32);
+        assert_eq!(first.record.moves.len(), 4);
+        assert_eq!(first.record.moves[3].turn, TurnPlayer::Light);
+        assert_eq!(
+            first
+                .record
+                .final_board()
+                .unwrap()
+                .count_disks(&Disk::Light),
+            4
+        );
+        assert!(games[1].as_ref().unwrap_err().starts_with("game 2: move 2"));
+    }
+
+    #[test]
+    fn test_names() {
+        let mut players = header(0, 2);
+        let mut name = b"Tastet Marc".to_vec();
+        name.resize(PLAYER_NAME_LEN, 0);
+        players.extend(&name);
+        let mut name = vec![b'L', 0xe9, b'v', b'y'];
+        name.resize(PLAYER_NAME_LEN, 0);
+        players.extend(&name);
+        let players = parse_players(&players).unwrap();
+        assert_eq!(players, vec!["Tastet Marc".to_string(), "Lévy".to_string()]);
+
+        let mut tournaments = header(0, 1);
+        let mut name = b"Paris Open".to_vec();
+        name.resize(TOURNAMENT_NAME_LEN, 0);
+        tournaments.extend(&name);
+        let tournaments = parse_tournaments(&tournaments).unwrap();
+
+        let mut bytes = header(1, 0);
+        bytes.extend(game(&[34]));
+        let wtb = Wtb::parse(&bytes).unwrap();
+        let game = wtb.games().next().unwrap().unwrap();
+        let game = game.with_names(&players, &tournaments);
+        assert_eq!(game.record.dark.name, "Tastet Marc");
+        assert_eq!(game.record.light.name, "Lévy");
+        assert_eq!(game.record.place.as_deref(), Some("Paris Open"));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut bytes = header(2, 0);
+        bytes.extend(game(&[34]));
+        assert!(Wtb::parse(&bytes).is_err());
+        assert!(Header::parse(&[20, 24]).is_err());
+    }
+}
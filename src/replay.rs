@@ -0,0 +1,244 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::Value;
+
+use crate::core::board::{Board, Disk, Position};
+use crate::io::color_cui::ColorCUI;
+use crate::io::json_log;
+use crate::record::{ggf, json, GameRecord, RecordedMove};
+
+const HELP: &str = "[Enter] next, p: previous, f: first, l: last, NUMBER: jump, q: quit";
+
+/// Reads a game in GGF, a JSON record or a JSON Lines log of `JsonLog`.
+pub fn load(source: &str) -> Result<GameRecord, String> {
+    let source = source.trim_start();
+    if source.starts_with("(;") {
+        return ggf::parse(source);
+    }
+
+    match serde_json::from_str::<Value>(source) {
+        Ok(value) if value.get("version").is_some() => json::parse(source),
+        _ => json_log::read_entries(source.as_bytes())
+            .and_then(|entries| json_log::to_record(&entries)),
+    }
+}
+
+/// Steps through the positions of a game record, which are rebuilt with
+/// `Board::place`.
+pub struct Replay {
+    record: GameRecord,
+    boards: Vec<Board>,
+    cursor: usize,
+}
+
+impl Replay {
+    pub fn new(record: GameRecord) -> Result<Self, String> {
+        let boards = record.boards()?;
+        Ok(Self {
+            record,
+            boards,
+            cursor: 0,
+        })
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    /// The number of moves, passes included.
+    pub fn len(&self) -> usize {
+        self.record.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.record.moves.is_empty()
+    }
+
+    /// The number of moves made to reach the current position.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.boards[self.cursor]
+    }
+
+    /// The move which has led to the current position.
+    pub fn last_move(&self) -> Option<&RecordedMove> {
+        self.cursor.checked_sub(1).map(|i| &self.record.moves[i])
+    }
+
+    /// The disks flipped by the last move.
+    pub fn flipped(&self) -> Vec<Position> {
+        let Some(prev) = self.cursor.checked_sub(1).map(|i| &self.boards[i]) else {
+            return Vec::new();
+        };
+
+        self.board()
+            .positions()
+            .filter(|pos| {
+                let before = prev.get(pos);
+                before.is_some() && before != self.board().get(pos)
+            })
+            .collect()
+    }
+
+    /// Returns the numbers of dark and light disks.
+    pub fn counts(&self) -> (usize, usize) {
+        (
+            self.board().count_disks(&Disk::Dark),
+            self.board().count_disks(&Disk::Light),
+        )
+    }
+
+    pub fn forward(&mut self) -> bool {
+        self.jump(self.cursor + 1).is_ok()
+    }
+
+    pub fn back(&mut self) -> bool {
+        match self.cursor.checked_sub(1) {
+            Some(cursor) => self.jump(cursor).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Moves to the position after `mov` moves, 0 for the start.
+    pub fn jump(&mut self, mov: usize) -> Result<(), String> {
+        if mov > self.len() {
+            return Err(format!("the game has only {} moves", self.len()));
+        }
+        self.cursor = mov;
+        Ok(())
+    }
+
+    pub fn render(&self, cui: &ColorCUI) -> String {
+        let header = match self.last_move() {
+            Some(mov) => {
+                let pos = mov
+                    .pos
+                    .as_ref()
+                    .map_or("pass".to_string(), Position::to_notation);
+                format!("Move {}/{}: {} {}", self.cursor, self.len(), mov.turn, pos)
+            }
+            None => format!("Start (0/{})", self.len()),
+        };
+        let header = match self.record.rules.is_standard() {
+            true => header,
+            false => format!("{} ({})", header, self.record.rules),
+        };
+        let last = self.last_move().and_then(|mov| mov.pos.as_ref());
+        let (dark, light) = self.counts();
+
+        format!(
+            "{}\n\n{}\n\n● {} - {} ○",
+            header,
+            cui.render_highlighted(self.board(), last, &self.flipped()),
+            dark,
+            light
+        )
+    }
+}
+
+/// Runs the viewer, reading commands from `input` until `q` or the end of
+/// the input.
+pub fn run<R: BufRead, W: Write>(
+    replay: &mut Replay,
+    cui: &ColorCUI,
+    input: R,
+    mut output: W,
+) -> io::Result<()> {
+    writeln!(output, "{}\n\n{}", HELP, replay.render(cui))?;
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let moved = match line.trim() {
+            "" | "n" => replay.forward(),
+            "p" => replay.back(),
+            "f" => replay.jump(0).is_ok(),
+            "l" => replay.jump(replay.len()).is_ok(),
+            "q" => return Ok(()),
+            "h" | "?" => {
+                writeln!(output, "{}", HELP)?;
+                false
+            }
+            num => match num.parse().map_err(|_| format!("unknown command: {}", num)) {
+                Ok(mov) => match replay.jump(mov) {
+                    Ok(()) => true,
+                    Err(msg) => {
+                        writeln!(output, "{}", msg)?;
+                        false
+                    }
+                },
+                Err(msg) => {
+                    writeln!(output, "{}\n{}", msg, HELP)?;
+                    false
+                }
+            },
+        };
+
+        if moved {
+            writeln!(output, "\n{}", replay.render(cui))?;
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME: &str = "(;GM[Othello]PB[a]PW[b]B[d3]W[c5]B[f6]W[f5];)";
+
+    #[test]
+    fn test_steps() {
+        let mut replay = Replay::new(load(GAME).unwrap()).unwrap();
+        assert_eq!(replay.len(), 4);
+        assert_eq!(replay.counts(), (2, 2));
+        assert!(!replay.back());
+
+        assert!(replay.forward());
+        assert_eq!(replay.counts(), (4, 1));
+        assert_eq!(replay.flipped(), vec![Position::new(3, 3)]);
+        assert_eq!(replay.last_move().unwrap().pos, Some(Position::new(3, 2)));
+
+        replay.jump(4).unwrap();
+        assert!(!replay.forward());
+        assert!(replay.jump(5).is_err());
+        assert!(replay.back());
+        assert_eq!(replay.cursor(), 3);
+    }
+
+    #[test]
+    fn test_run() {
+        let mut replay = Replay::new(load(GAME).unwrap()).unwrap();
+        let cui = ColorCUI::with_color(false);
+        let mut output = Vec::new();
+        run(
+            &mut replay,
+            &cui,
+            "\n\nx\np\n9\nq\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Move 2/4: Light c5"));
+        assert!(output.contains("● 3 - 3 ○"));
+        assert!(output.contains("unknown command: x"));
+        assert!(output.contains("the game has only 4 moves"));
+        assert!(output.contains("3   _ _ _*● _ _ _ _\n4   _ _ _+● ● _ _ _"));
+        assert_eq!(replay.cursor(), 1);
+    }
+
+    #[test]
+    fn test_load_formats() {
+        let record = load(GAME).unwrap();
+        assert_eq!(load(&json::to_string(&record)), Ok(record));
+        assert!(load("nothing").is_err());
+    }
+}
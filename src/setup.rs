@@ -0,0 +1,296 @@
+use std::io::{self, BufRead, Write};
+
+use crate::ai::{self, ENDGAME_EMPTIES};
+use crate::analysis;
+use crate::core::board::{Board, BoardState, Disk, Position};
+use crate::core::rules::RuleSet;
+use crate::core::ruversi::TurnPlayer;
+use crate::io::color_cui::ColorCUI;
+
+const HELP: &str = "commands:
+  x SQUARE...     place dark disks, e.g. x d3 e4
+  o SQUARE...     place light disks
+  - SQUARE...     remove disks
+  turn x|o        choose the side to move
+  clear | reset   empty the board, or set up the starting position
+  load POSITION   the squares row by row from a1, 64 on the standard board
+                  or N*N on an N by N board, and optionally the side to
+                  move, e.g. ---------------------------OX------XO--------------------------- X
+  analyze [N]     score the moves of the side to move, searching N plies
+  play            start a game from the position
+  quit            leave without playing";
+
+/// Checks that a game can be played from a position: the center squares are
+/// occupied as in any game, and a player still has a legal move.
+pub fn validate(board: &Board, turn: TurnPlayer) -> Result<(), String> {
+    let (low, high) = (board.size() as i32 / 2 - 1, board.size() as i32 / 2);
+    let center = [(low, low), (high, low), (low, high), (high, high)];
+    if center
+        .iter()
+        .any(|&(x, y)| board.get(&Position::new(x, y)).is_none())
+    {
+        return Err("the four center squares must be occupied".to_string());
+    }
+
+    let disk = turn.into_disk();
+    if !board.exists_legal_mov(disk)
+        && !board.exists_legal_mov(TurnPlayer::other(&turn).into_disk())
+    {
+        return Err("the game is already over".to_string());
+    }
+
+    Ok(())
+}
+
+/// Parses `x`, `o`, `dark` or `light` as the side to move.
+pub fn parse_turn(source: &str) -> Result<TurnPlayer, String> {
+    match source.to_ascii_lowercase().as_str() {
+        "x" | "dark" | "b" | "black" => Ok(TurnPlayer::Dark),
+        "o" | "light" | "w" | "white" => Ok(TurnPlayer::Light),
+        _ => Err(format!("unknown side: {}", source)),
+    }
+}
+
+/// A position being edited in the setup mode.
+pub struct Setup {
+    pub board: Board,
+    pub turn: TurnPlayer,
+    depth: u32,
+    rules: RuleSet,
+}
+
+/// What to do after the setup mode.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SetupResult {
+    Play(Board, TurnPlayer),
+    Quit,
+}
+
+impl Setup {
+    pub fn new(board: Board, turn: TurnPlayer, depth: u32) -> Self {
+        Self {
+            board,
+            turn,
+            depth,
+            rules: RuleSet::Standard,
+        }
+    }
+
+    /// Sets the rules which `analyze` scores the moves by.
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+    }
+
+    pub fn render(&self, cui: &ColorCUI) -> String {
+        let note = match self.board.exists_legal_mov(self.turn.into_disk()) {
+            true => "",
+            false => " (has to pass)",
+        };
+        format!(
+            "{}\n\n● {} - {} ○, {} to move{}\n{}",
+            cui.render_highlighted(&self.board, None, &[]),
+            self.board.count_disks(&Disk::Dark),
+            self.board.count_disks(&Disk::Light),
+            self.turn,
+            note,
+            BoardState::new(self.board.clone(), self.turn)
+        )
+    }
+
+    /// Scores the moves of the side to move, the best first.
+    pub fn analyze(&self, depth: u32) -> Result<String, String> {
+        validate(&self.board, self.turn)?;
+        let ranked = analysis::rank_movs(&self.board, self.turn, depth, self.rules);
+        if ranked.is_empty() {
+            return Ok(format!("{} has to pass", self.turn));
+        }
+
+        let unit = match ai::count_empties(&self.board) <= ENDGAME_EMPTIES {
+            true => " disks",
+            false => "",
+        };
+        Ok(ranked
+            .iter()
+            .map(|(pos, score)| format!("{} {:+}{}", pos.to_notation(), score, unit))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Applies a command, and returns whether the board is to be shown again
+    /// or the setup has finished.
+    fn apply(&mut self, line: &str) -> Result<Option<SetupResult>, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+
+        match command {
+            "x" | "o" | "-" | "_" => {
+                if args.is_empty() {
+                    return Err(format!("{} needs squares such as d3", command));
+                }
+                let squares = args
+                    .iter()
+                    .map(|arg| match Position::try_from_notation(arg) {
+                        Ok(pos) if self.board.contains(&pos) => Ok(pos),
+                        Ok(_) => Err(format!("{}: off the board", arg)),
+                        Err(msg) => Err(format!("{}: {}", arg, msg)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                for pos in squares {
+                    match command {
+                        "x" => self.board.set(&pos, Disk::Dark),
+                        "o" => self.board.set(&pos, Disk::Light),
+                        _ => self.board.remove(&pos),
+                    }
+                }
+            }
+            "turn" => self.turn = parse_turn(args.first().ok_or("turn needs x or o")?)?,
+            "clear" => self.board = Board::with_size(self.board.size()).unwrap(),
+            "reset" => {
+                self.board = Board::initial_with_size(self.board.size()).unwrap();
+                self.turn = TurnPlayer::Dark;
+            }
+            "load" => {
+                let state =
+                    BoardState::parse_or(&args.join(" "), self.turn).map_err(|e| e.to_string())?;
+                self.board = state.board;
+                self.turn = state.turn;
+            }
+            "play" => {
+                validate(&self.board, self.turn)?;
+                return Ok(Some(SetupResult::Play(self.board.clone(), self.turn)));
+            }
+            "quit" | "q" => return Ok(Some(SetupResult::Quit)),
+            _ => return Err(format!("unknown command: {}", command)),
+        }
+
+        Ok(None)
+    }
+
+    /// Edits the position with commands from `input` until `play`, `quit` or
+    /// the end of the input.
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        cui: &ColorCUI,
+        input: R,
+        mut output: W,
+    ) -> io::Result<SetupResult> {
+        writeln!(output, "{}\n\n{}", HELP, self.render(cui))?;
+        write!(output, "> ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (None, _) => (),
+                (Some("help" | "?"), _) => writeln!(output, "{}", HELP)?,
+                (Some("analyze"), depth) => {
+                    let depth = depth.and_then(|d| d.parse().ok()).unwrap_or(self.depth);
+                    match self.analyze(depth) {
+                        Ok(text) => writeln!(output, "{}", text)?,
+                        Err(msg) => writeln!(output, "{}", msg)?,
+                    }
+                }
+                _ => match self.apply(&line) {
+                    Ok(Some(result)) => return Ok(result),
+                    Ok(None) => writeln!(output, "\n{}", self.render(cui))?,
+                    Err(msg) => writeln!(output, "{}", msg)?,
+                },
+            }
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+
+        Ok(SetupResult::Quit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(setup: &mut Setup, input: &str) -> (SetupResult, String) {
+        let mut output = Vec::new();
+        let result = setup
+            .run(&ColorCUI::with_color(false), input.as_bytes(), &mut output)
+            .unwrap();
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_edit_and_play() {
+        let mut setup = Setup::new(Board::initial(), TurnPlayer::Dark, 1);
+        let (result, output) = run(&mut setup, "x d4 c3\n- c3\nturn o\nz9\nplay\n");
+
+        let mut board = Board::initial();
+        board.set(&Position::new(3, 3), Disk::Dark);
+        assert_eq!(result, SetupResult::Play(board, TurnPlayer::Light));
+        assert!(output.contains("● 3 - 1 ○, Light to move\n"));
+        assert!(output.contains("unknown command: z9"));
+    }
+
+    #[test]
+    fn test_load() {
+        let mut setup = Setup::new(Board::new(), TurnPlayer::Dark, 1);
+        let position = "---------------------------OX------XO--------------------------- O";
+        let (_, output) = run(&mut setup, &format!("load {}\nload ---?\n", position));
+
+        assert_eq!(setup.board, Board::initial());
+        assert_eq!(setup.turn, TurnPlayer::Light);
+        assert!(output.contains(position));
+        assert!(output.contains("column 4: unknown square '?'"));
+    }
+
+    #[test]
+    fn test_load_other_size() {
+        let mut setup = Setup::new(Board::with_size(6).unwrap(), TurnPlayer::Dark, 1);
+        let state = BoardState::new(Board::initial_with_size(6).unwrap(), TurnPlayer::Light);
+        run(&mut setup, &format!("load {}\n", state));
+        assert_eq!(setup.board, state.board);
+        assert_eq!(setup.turn, TurnPlayer::Light);
+
+        // What is shown can be loaded back.
+        let mut loaded = Setup::new(Board::new(), TurnPlayer::Dark, 1);
+        let shown = setup.render(&ColorCUI::with_color(false));
+        run(
+            &mut loaded,
+            &format!("load {}\n", shown.lines().last().unwrap()),
+        );
+        assert_eq!(loaded.board, setup.board);
+        assert_eq!(loaded.turn, setup.turn);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut setup = Setup::new(Board::new(), TurnPlayer::Dark, 1);
+        let (result, output) = run(&mut setup, "play\nx d4 e4 d5 e5\nplay\nreset\nanalyze\n");
+
+        assert_eq!(result, SetupResult::Quit);
+        assert!(output.contains("the four center squares must be occupied"));
+        assert!(output.contains("the game is already over"));
+        assert!(output.contains("d3 +"));
+        assert_eq!(setup.board, Board::initial());
+    }
+
+    #[test]
+    fn test_other_size() {
+        let mut setup = Setup::new(Board::with_size(6).unwrap(), TurnPlayer::Dark, 1);
+        let (result, output) = run(&mut setup, "x g1\nx c3 d4\nplay\nreset\nplay\n");
+
+        assert!(output.contains("g1: off the board"));
+        assert!(output.contains("--------------OX----XO-------------- X"));
+        assert!(output.contains("the four center squares must be occupied"));
+        assert_eq!(
+            result,
+            SetupResult::Play(Board::initial_with_size(6).unwrap(), TurnPlayer::Dark)
+        );
+    }
+
+    #[test]
+    fn test_parse_turn() {
+        assert_eq!(parse_turn("O"), Ok(TurnPlayer::Light));
+        assert_eq!(parse_turn("dark"), Ok(TurnPlayer::Dark));
+        assert!(parse_turn("red").is_err());
+    }
+}